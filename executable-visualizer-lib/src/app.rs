@@ -1,6 +1,9 @@
-use crate::file_loader::FileLoader;
+//! A complete [`eframe::App`] built around the inspector, used by the native and web binaries.
+
+use crate::file_loader::{FileLoader, LoadAs};
 use crate::sections::ExecutableFile;
 
+/// Handles loading files from the user and displays them in the inspector.
 #[derive(Default)]
 pub struct ExampleApp {
     /// Options for configuring how the Inspector is displayed.
@@ -8,32 +11,417 @@ pub struct ExampleApp {
 
     pub files: Vec<ExecutableFile>,
     file_loader: FileLoader,
+
+    /// Text entered for the offset of an embedded executable to load.
+    embedded_offset: String,
+    /// Text entered for the length of an embedded executable to load, empty for the rest of the file.
+    embedded_len: String,
+    /// Text entered for the address a raw image is loaded at.
+    raw_base_address: String,
+
+    /// Progress of exporting the canvas as an image, see [`ImageExport`].
+    image_export: Option<ImageExport>,
+    /// Size of the exported image relative to the canvas on screen.
+    image_scale: ImageScale,
+
+    /// The size by source file report of each file, shown in a window while set.
+    #[cfg(feature = "dwarf")]
+    source_file_sizes: Option<Vec<(String, anyhow::Result<Vec<crate::dwarf::SourceFileSize>>)>>,
 }
 
 impl ExampleApp {
     pub fn new(files: Vec<ExecutableFile>) -> Self {
+        Self::with_options(files, crate::inspector::Options::default())
+    }
+
+    /// Same as [`ExampleApp::new`] but starts with `inspector_options` instead of the defaults,
+    /// e.g. from [`crate::inspector::Options::from_args`].
+    pub fn with_options(
+        files: Vec<ExecutableFile>,
+        mut inspector_options: crate::inspector::Options,
+    ) -> Self {
+        for file in &files {
+            inspector_options.zoom_to_initial_section(file, 0.0);
+        }
         ExampleApp {
-            inspector_options: Default::default(),
+            inspector_options,
             files,
             file_loader: FileLoader::default(),
+            embedded_offset: String::new(),
+            embedded_len: String::new(),
+            raw_base_address: String::new(),
+            image_export: None,
+            image_scale: ImageScale::default(),
+            #[cfg(feature = "dwarf")]
+            source_file_sizes: None,
+        }
+    }
+}
+
+/// Key [`ExampleApp::inspector_options`] is saved under in the [`eframe::Storage`].
+#[cfg(feature = "persistence")]
+const OPTIONS_STORAGE_KEY: &str = "inspector_options";
+
+impl ExampleApp {
+    /// Same as [`ExampleApp::new`] but starts with the options saved by the last session in `storage`, if any.
+    /// Pass [`eframe::CreationContext::storage`].
+    #[cfg(feature = "persistence")]
+    pub fn restore(files: Vec<ExecutableFile>, storage: Option<&dyn eframe::Storage>) -> Self {
+        let inspector_options = storage
+            .and_then(|storage| eframe::get_value(storage, OPTIONS_STORAGE_KEY))
+            .unwrap_or_default();
+        Self::with_options(files, inspector_options)
+    }
+}
+
+impl ExampleApp {
+    /// The offset and length entered for loading an embedded executable.
+    fn embedded_range(&self) -> Result<LoadAs, String> {
+        let offset = if self.embedded_offset.trim().is_empty() {
+            0
+        } else {
+            parse_number(&self.embedded_offset)?
+        };
+        let len = if self.embedded_len.trim().is_empty() {
+            None
+        } else {
+            Some(parse_number(&self.embedded_len)?)
+        };
+        Ok(LoadAs::Embedded { offset, len })
+    }
+
+    /// The base address entered for loading a raw image.
+    fn raw_base_address(&self) -> Result<LoadAs, String> {
+        let base_address = if self.raw_base_address.trim().is_empty() {
+            0
+        } else {
+            parse_number(&self.raw_base_address)?
+        };
+        Ok(LoadAs::Raw { base_address })
+    }
+
+    #[cfg(feature = "dwarf")]
+    fn display_source_file_sizes(&mut self, ui: &mut egui::Ui) {
+        let Some(reports) = &self.source_file_sizes else {
+            return;
+        };
+        let mut window_open = true;
+        egui::Window::new("Size by source file")
+            .open(&mut window_open)
+            .vscroll(true)
+            .show(ui.ctx(), |ui| {
+                for (name, report) in reports {
+                    egui::CollapsingHeader::new(name)
+                        .default_open(reports.len() == 1)
+                        .show(ui, |ui| match report {
+                            Ok(sizes) if sizes.is_empty() => {
+                                ui.label("The line tables do not describe any code.");
+                            }
+                            Ok(sizes) => {
+                                egui::Grid::new(name).striped(true).show(ui, |ui| {
+                                    for size in sizes {
+                                        ui.monospace(&size.path);
+                                        ui.monospace(format!("{}", size.bytes));
+                                        ui.end_row();
+                                    }
+                                });
+                            }
+                            Err(err) => {
+                                ui.label(format!("{err:#}"));
+                            }
+                        });
+                }
+            });
+        if !window_open {
+            self.source_file_sizes = None;
+        }
+    }
+}
+
+/// The steps of exporting the canvas as a PNG, each taking a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ImageExport {
+    /// Waiting for a frame without the menu the export was started from.
+    /// `frame_nr` is the frame the menu was still painted in, see [`egui::Context::frame_nr`].
+    Requested { frame_nr: u64 },
+    /// A screenshot was requested and will arrive as an [`egui::Event::Screenshot`].
+    Capturing,
+}
+
+impl ExampleApp {
+    /// Advances [`ExampleApp::image_export`], once the screenshot arrives the canvas is cut out of it and saved.
+    fn export_image(&mut self, ui: &mut egui::Ui) {
+        match self.image_export {
+            None => {}
+            Some(ImageExport::Requested { frame_nr }) => {
+                if ui.ctx().frame_nr() <= frame_nr {
+                    ui.ctx().request_repaint();
+                    return;
+                }
+                ui.ctx()
+                    .send_viewport_cmd(egui::ViewportCommand::Screenshot);
+                self.image_export = Some(ImageExport::Capturing);
+            }
+            Some(ImageExport::Capturing) => {
+                let screenshot = ui.input(|i| {
+                    i.events.iter().find_map(|event| match event {
+                        egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                        _ => None,
+                    })
+                });
+                let Some(screenshot) = screenshot else {
+                    return;
+                };
+                self.image_export = None;
+                let image = match self.inspector_options.canvas_rect() {
+                    Some(rect) => screenshot.region(&rect, Some(ui.ctx().pixels_per_point())),
+                    None => (*screenshot).clone(),
+                };
+                let image = match self.image_scale {
+                    ImageScale::Screen => image,
+                    scale => resize(&image, scale.factor()),
+                };
+                match encode_png(&image) {
+                    Ok(png) => {
+                        self.file_loader
+                            .save_file_from_user(ui, "executable-visualizer.png", png)
+                    }
+                    Err(err) => self.file_loader.show_error(format!("{err:?}")),
+                }
+            }
+        }
+    }
+}
+
+/// Size of an exported image relative to the canvas on screen, see [`ExampleApp::export_image`].
+/// Images are captured from the screen, so other sizes are resampled from the screen's pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ImageScale {
+    Half,
+    #[default]
+    Screen,
+    Double,
+    Quadruple,
+}
+
+impl ImageScale {
+    const ALL: [ImageScale; 4] = [
+        ImageScale::Half,
+        ImageScale::Screen,
+        ImageScale::Double,
+        ImageScale::Quadruple,
+    ];
+
+    fn factor(self) -> f32 {
+        match self {
+            ImageScale::Half => 0.5,
+            ImageScale::Screen => 1.0,
+            ImageScale::Double => 2.0,
+            ImageScale::Quadruple => 4.0,
+        }
+    }
+}
+
+/// Scales `image` by `factor` with bilinear filtering.
+fn resize(image: &egui::ColorImage, factor: f32) -> egui::ColorImage {
+    let [width, height] = image.size;
+    let size = [
+        ((width as f32 * factor).round() as usize).max(1),
+        ((height as f32 * factor).round() as usize).max(1),
+    ];
+    let pixel = |x: usize, y: usize| {
+        egui::Rgba::from(image.pixels[y.min(height - 1) * width + x.min(width - 1)])
+    };
+    let mut resized = egui::ColorImage::new(size, egui::Color32::TRANSPARENT);
+    if width == 0 || height == 0 {
+        return resized;
+    }
+    for y in 0..size[1] {
+        // The centers of the pixels line up, so neither edge is cut off.
+        let source_y = ((y as f32 + 0.5) / factor - 0.5).max(0.0);
+        let (y0, fy) = (source_y as usize, source_y.fract());
+        for x in 0..size[0] {
+            let source_x = ((x as f32 + 0.5) / factor - 0.5).max(0.0);
+            let (x0, fx) = (source_x as usize, source_x.fract());
+            let top = pixel(x0, y0) * (1.0 - fx) + pixel(x0 + 1, y0) * fx;
+            let bottom = pixel(x0, y0 + 1) * (1.0 - fx) + pixel(x0 + 1, y0 + 1) * fx;
+            resized.pixels[y * size[0] + x] = (top * (1.0 - fy) + bottom * fy).into();
         }
     }
+    resized
+}
+
+/// Encodes `image` as an 8 bit RGBA PNG.
+fn encode_png(image: &egui::ColorImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, image.size[0] as u32, image.size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(image.as_raw())?;
+    writer.finish()?;
+    Ok(png)
+}
+
+/// Parses a decimal or `0x` prefixed hex number.
+fn parse_number(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let result = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    result.map_err(|err| format!("Invalid number {text:?}: {err}"))
 }
 
 impl eframe::App for ExampleApp {
+    /// Saves the options, the transient parts like the selection are skipped by their serde attributes.
+    #[cfg(feature = "persistence")]
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, OPTIONS_STORAGE_KEY, &self.inspector_options);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             // TODO: actual UI should go inline:
             //       --------
             //       v new file
-            //            Load file from disk       Load file from preset      Or just drag file onto window
-            ui.menu_button("File", |ui| {
-                if ui.button("Load file").clicked() {
-                    self.file_loader.request_file_from_user(ui);
+            //            Load file from disk       Load file from preset
+            //       Files dropped onto the window are already loaded, see `paint_drop_overlay`.
+            ui.horizontal(|ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("Load file").clicked() {
+                        self.file_loader
+                            .request_file_from_user(ui, LoadAs::Executable);
+                    }
+                    if ui.button("Find embedded files").clicked() {
+                        self.file_loader.request_scan_from_user(ui);
+                        ui.close_menu();
+                    }
+                    ui.menu_button("Load embedded file", |ui| {
+                        egui::Grid::new("embedded_file").show(ui, |ui| {
+                            ui.label("offset");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.embedded_offset)
+                                    .hint_text("0x0"),
+                            );
+                            ui.end_row();
+
+                            ui.label("length");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.embedded_len)
+                                    .hint_text("rest of file"),
+                            );
+                            ui.end_row();
+                        });
+                        if ui.button("Load file").clicked() {
+                            match self.embedded_range() {
+                                Ok(load_as) => self.file_loader.request_file_from_user(ui, load_as),
+                                Err(err) => self.file_loader.show_error(err),
+                            }
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button("Load raw image", |ui| {
+                        ui.label(
+                            "For files without any executable structure, e.g. firmware dumps.",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label("base address");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.raw_base_address)
+                                    .hint_text("0x0"),
+                            );
+                        });
+                        if ui.button("Load file").clicked() {
+                            match self.raw_base_address() {
+                                Ok(load_as) => self.file_loader.request_file_from_user(ui, load_as),
+                                Err(err) => self.file_loader.show_error(err),
+                            }
+                            ui.close_menu();
+                        }
+                    });
+                    if ui
+                        .add_enabled(!self.files.is_empty(), egui::Button::new("Export CSV"))
+                        .on_hover_text("Save the size of every section of each file as a CSV table")
+                        .clicked()
+                    {
+                        let csv = crate::report::csv(&self.files);
+                        self.file_loader
+                            .save_file_from_user(ui, "sections.csv", csv.into_bytes());
+                        ui.close_menu();
+                    }
+                    // The browser version of eframe can't take screenshots.
+                    let can_export_image = !cfg!(target_arch = "wasm32");
+                    if ui
+                        .add_enabled(
+                            can_export_image && !self.files.is_empty(),
+                            egui::Button::new("Export image"),
+                        )
+                        .on_hover_text(
+                            "Save the scopes and timeline as they are shown now as a PNG, at the scale below",
+                        )
+                        .on_disabled_hover_text("Not supported in the browser")
+                        .clicked()
+                    {
+                        self.image_export = Some(ImageExport::Requested {
+                            frame_nr: ui.ctx().frame_nr(),
+                        });
+                        ui.ctx().request_repaint();
+                        ui.close_menu();
+                    }
+                    if can_export_image {
+                        ui.horizontal(|ui| {
+                            ui.label("image scale");
+                            for scale in ImageScale::ALL {
+                                let text = format!("{}%", (scale.factor() * 100.0) as u32);
+                                ui.selectable_value(&mut self.image_scale, scale, text)
+                                    .on_hover_text("Sizes other than 100% are resampled from the screen");
+                            }
+                        });
+                    }
+                });
+                #[cfg(feature = "dwarf")]
+                if ui
+                    .button("Size by source file")
+                    .on_hover_text(
+                        "How much code each source file contributed, from the DWARF line tables",
+                    )
+                    .clicked()
+                {
+                    self.source_file_sizes = Some(
+                        self.files
+                            .iter()
+                            .map(|file| {
+                                (file.name.clone(), crate::dwarf::size_by_source_file(file))
+                            })
+                            .collect(),
+                    );
+                }
+                if self.file_loader.is_loading() {
+                    // The spinner keeps requesting repaints while it is shown, so it animates
+                    // even while no input arrives, until the background task requests the last one.
+                    ui.spinner();
+                    ui.label(match self.file_loader.loading_count() {
+                        1 => "Loading…".to_owned(),
+                        count => format!("Loading {count} files…"),
+                    });
+                    if ui.button("Cancel loading").clicked() {
+                        self.file_loader.cancel();
+                    }
                 }
             });
+            self.export_image(ui);
             self.file_loader.display_error(ui);
-            if let Some(file) = self.file_loader.recive_file_from_user() {
+            self.file_loader.display_scan(ui);
+            #[cfg(feature = "dwarf")]
+            self.display_source_file_sizes(ui);
+            let dropped_files = ctx.input_mut(|i| std::mem::take(&mut i.raw.dropped_files));
+            if !dropped_files.is_empty() {
+                self.file_loader.load_dropped_files(ui, dropped_files);
+            }
+            let time = ctx.input(|i| i.time);
+            for file in self.file_loader.recive_files_from_user() {
+                self.inspector_options.zoom_to_initial_section(&file, time);
                 self.files.push(file);
             }
 
@@ -41,5 +429,46 @@ impl eframe::App for ExampleApp {
 
             crate::inspector::ui(ui, &mut self.inspector_options, &mut self.files)
         });
+        paint_drop_overlay(ctx);
+    }
+}
+
+/// Darkens the window while files are dragged over it, to show that dropping them loads them.
+fn paint_drop_overlay(ctx: &egui::Context) {
+    let hovered = ctx.input(|i| i.raw.hovered_files.len());
+    if hovered == 0 {
+        return;
+    }
+    let text = match hovered {
+        1 => "Drop to load file".to_owned(),
+        count => format!("Drop to load {count} files"),
+    };
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("drop_overlay"),
+    ));
+    let screen_rect = ctx.screen_rect();
+    painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(192));
+    painter.text(
+        screen_rect.center(),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::TextStyle::Heading.resolve(&ctx.style()),
+        egui::Color32::WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_keeps_colors_and_scales_size() {
+        let red = egui::Color32::from_rgb(255, 0, 0);
+        let image = egui::ColorImage::new([3, 2], red);
+        let doubled = resize(&image, 2.0);
+        assert_eq!(doubled.size, [6, 4]);
+        assert!(doubled.pixels.iter().all(|pixel| *pixel == red));
+        assert_eq!(resize(&image, 0.5).size, [2, 1]);
     }
 }
@@ -0,0 +1,249 @@
+//! Comparing the sections of executables against a baseline, e.g. the previous build of the same program.
+
+use crate::sections::{ExecutableFile, FileNode, Layout, SectionTypeCategory};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// How a [`FileNode`] compares to the matching node in a [`Baseline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeDiff {
+    /// The baseline has no matching node.
+    Added,
+    /// Same size and same contents as in the baseline.
+    Identical,
+    /// The contents differ, `delta` is the size minus the size in the baseline and may be 0.
+    Changed { delta: i64 },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BaselineNode {
+    len: u64,
+    hash: u32,
+}
+
+/// One of [`ExecutableFile::sections_iter`] of the baseline, for finding the sections other files no longer have.
+#[derive(Clone, Debug)]
+struct BaselineSection {
+    name: String,
+    occurrence: usize,
+    len: u64,
+}
+
+/// The overall difference between a file and a [`Baseline`], see [`Baseline::summary`].
+#[derive(Clone, Debug)]
+pub struct DiffSummary {
+    /// Size of the file minus the size of the baseline.
+    pub total_delta: i64,
+    /// The sections that grew the most, or are new, with how much they grew, largest first.
+    pub growers: Vec<(String, NodeDiff)>,
+    /// The sections of the baseline that the file doesn't have, with their size in the baseline, largest first.
+    pub removed: Vec<(String, u64)>,
+}
+
+/// The sizes and content hashes of every node of a file, for comparing other files against.
+///
+/// Nodes are matched by name and [`FileNode::occurrence`] within the same [`Layout`],
+/// so when several sections share a name the first is compared to the first, the second to the second and so on.
+#[derive(Clone)]
+pub struct Baseline {
+    name: String,
+    data: Arc<[u8]>,
+    len: u64,
+    nodes: HashMap<Layout, HashMap<String, Vec<BaselineNode>>>,
+    sections: Vec<BaselineSection>,
+}
+
+impl Baseline {
+    pub fn new(file: &ExecutableFile) -> Self {
+        let mut nodes = HashMap::new();
+        for layout in [Layout::File, Layout::Ram] {
+            let layout_nodes = nodes.entry(layout).or_default();
+            collect_nodes(file.root(layout), &file.data, layout_nodes);
+        }
+        let sections = file
+            .sections_iter()
+            .map(|section| BaselineSection {
+                name: section.name.clone(),
+                occurrence: section.occurrence(),
+                len: section.len(),
+            })
+            .collect();
+        Baseline {
+            name: file.name.clone(),
+            data: file.data.clone(),
+            len: file.file_root.len(),
+            nodes,
+            sections,
+        }
+    }
+
+    /// Name of the file this baseline was created from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if `file` is the file this baseline was created from.
+    pub fn is_file(&self, file: &ExecutableFile) -> bool {
+        Arc::ptr_eq(&self.data, &file.data)
+    }
+
+    /// Compare `node` from the `layout` tree of a file with the contents `data` against the baseline.
+    /// Zero initialized nodes like `.bss` have no contents, they are identical if their size is.
+    pub fn compare(&self, layout: Layout, node: &FileNode, data: &[u8]) -> NodeDiff {
+        let baseline = self
+            .nodes
+            .get(&layout)
+            .and_then(|nodes| nodes.get(&node.name))
+            .and_then(|occurrences| occurrences.get(node.occurrence()));
+        match baseline {
+            None => NodeDiff::Added,
+            Some(baseline) => {
+                let same_contents = node.category() == SectionTypeCategory::Bss
+                    || baseline.hash == node.content_hash(data);
+                if baseline.len == node.len() && same_contents {
+                    NodeDiff::Identical
+                } else {
+                    NodeDiff::Changed {
+                        delta: node.len() as i64 - baseline.len as i64,
+                    }
+                }
+            }
+        }
+    }
+
+    /// How `file` differs from the baseline overall, only [`ExecutableFile::sections_iter`] are listed,
+    /// so a symbol or relocation table is listed as a whole rather than by its entries.
+    /// At most `count` growers are listed.
+    pub fn summary(&self, file: &ExecutableFile, count: usize) -> DiffSummary {
+        let mut growers: Vec<(String, NodeDiff, i64)> = file
+            .sections_iter()
+            .filter_map(|section| {
+                let diff = self.compare(Layout::File, section, &file.data);
+                let delta = match diff {
+                    NodeDiff::Added => section.len() as i64,
+                    NodeDiff::Identical => 0,
+                    NodeDiff::Changed { delta } => delta,
+                };
+                (delta > 0).then(|| (section.name.clone(), diff, delta))
+            })
+            .collect();
+        growers.sort_by_key(|(_, _, delta)| std::cmp::Reverse(*delta));
+        growers.truncate(count);
+
+        // A section is removed when the file has fewer nodes of that name than the baseline.
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for node in file.file_root.iter() {
+            *counts.entry(&node.name).or_default() += 1;
+        }
+        let mut removed: Vec<(String, u64)> = self
+            .sections
+            .iter()
+            .filter(|section| {
+                counts.get(section.name.as_str()).copied().unwrap_or(0) <= section.occurrence
+            })
+            .map(|section| (section.name.clone(), section.len))
+            .collect();
+        removed.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
+
+        DiffSummary {
+            total_delta: file.file_root.len() as i64 - self.len as i64,
+            growers: growers
+                .into_iter()
+                .map(|(name, diff, _)| (name, diff))
+                .collect(),
+            removed,
+        }
+    }
+}
+
+/// Leaves out the nodes and the raw bytes of the file.
+impl fmt::Debug for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Baseline")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+fn collect_nodes(node: &FileNode, data: &[u8], nodes: &mut HashMap<String, Vec<BaselineNode>>) {
+    // Nodes are visited depth first, the same order their occurrences are numbered in.
+    nodes
+        .entry(node.name.clone())
+        .or_default()
+        .push(BaselineNode {
+            len: node.len(),
+            hash: node.content_hash(data),
+        });
+    for child in &node.children {
+        collect_nodes(child, data, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sections::test_elf::TestElf;
+    use goblin::elf::{
+        section_header::{SHT_PROGBITS, SHT_STRTAB, SHT_SYMTAB},
+        sym::{STB_GLOBAL, STT_FUNC},
+    };
+
+    /// A file with a section named `.data` for each of `contents`.
+    fn load(contents: &[Vec<u8>]) -> ExecutableFile {
+        let mut elf = TestElf::new_64();
+        for contents in contents {
+            elf.section(".data", SHT_PROGBITS, contents.clone());
+        }
+        ExecutableFile::load_from_bytes("test".into(), &elf.build()).unwrap()
+    }
+
+    #[test]
+    fn duplicate_names_are_matched_by_occurrence() {
+        let baseline = Baseline::new(&load(&[vec![1; 0x10], vec![2; 0x10]]));
+
+        let file = load(&[vec![1; 0x10], vec![3; 0x20]]);
+        let compare = |occurrence| {
+            let node = file
+                .file_root
+                .find_section_occurrence(".data", occurrence)
+                .unwrap();
+            baseline.compare(Layout::File, node, &file.data)
+        };
+        assert_eq!(compare(0), NodeDiff::Identical);
+        assert_eq!(compare(1), NodeDiff::Changed { delta: 0x10 });
+
+        let file = load(&[vec![1; 0x10]]);
+        let summary = baseline.summary(&file, 10);
+        assert_eq!(summary.removed, [(".data".to_owned(), 0x10)]);
+    }
+
+    /// A file with a symbol table of `count` symbols.
+    fn load_symbols(count: u32) -> ExecutableFile {
+        let mut elf = TestElf::new_64();
+        let strtab = elf.section(".strtab", SHT_STRTAB, b"\0f\0".to_vec());
+        let symbols: Vec<_> = (0..count)
+            .map(|i| (1, (STB_GLOBAL << 4) | STT_FUNC, 0, i as u64, 0))
+            .collect();
+        let symbols = elf.symbols(&symbols);
+        elf.section(".symtab", SHT_SYMTAB, symbols);
+        elf.last().sh_link = strtab;
+        elf.last().sh_entsize = 24;
+        ExecutableFile::load_from_bytes("test".into(), &elf.build()).unwrap()
+    }
+
+    #[test]
+    fn summary_lists_tables_rather_than_their_entries() {
+        let baseline = Baseline::new(&load_symbols(3));
+
+        let summary = baseline.summary(&load_symbols(5), 10);
+        let growers: Vec<&str> = summary
+            .growers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert!(growers.contains(&".symtab"), "{growers:?}");
+        assert!(!growers.contains(&"f"), "{growers:?}");
+
+        let summary = baseline.summary(&load_symbols(1), 10);
+        assert!(summary.removed.iter().all(|(name, _)| name != "f"));
+    }
+}
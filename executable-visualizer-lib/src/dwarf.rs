@@ -0,0 +1,191 @@
+//! Attributing code to the source files it was compiled from, using the DWARF line tables in `.debug_line`.
+//!
+//! The line tables are read with [`gimli`], only the file of each row is used, line and column numbers are ignored.
+
+use crate::sections::{ExecutableFile, FileNode, SectionType, SectionTypeCategory};
+use anyhow::{anyhow, Context, Result};
+use gimli::{EndianSlice, RunTimeEndian};
+use goblin::{
+    container::{Container, Ctx, Endian},
+    elf::{
+        compression_header::{CompressionHeader, ELFCOMPRESS_ZLIB},
+        section_header::SHF_COMPRESSED,
+        Elf,
+    },
+};
+use std::{borrow::Cow, collections::HashMap};
+
+/// How many bytes of code were generated from a source file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceFileSize {
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Sums the address ranges described by the line tables of `file` per source file, largest first.
+///
+/// Returns an error if `file` has no `.debug_line` section, e.g. because it was built without debug info.
+pub fn size_by_source_file(file: &ExecutableFile) -> Result<Vec<SourceFileSize>> {
+    if file.file_root.find_section(".debug_line").is_none() {
+        return Err(anyhow!(
+            "{} has no .debug_line section, it may have been built without debug info.",
+            file.name
+        ));
+    }
+    // Only ELF files say how they are encoded, the other formats are little endian in practice.
+    let ctx = Elf::parse_header(&file.data)
+        .ok()
+        .and_then(|header| {
+            Some(Ctx::new(
+                header.container().ok()?,
+                header.endianness().ok()?,
+            ))
+        })
+        .unwrap_or(Ctx::new(Container::Big, Endian::Little));
+    let endian = if ctx.is_little_endian() {
+        RunTimeEndian::Little
+    } else {
+        RunTimeEndian::Big
+    };
+    let sections = gimli::DwarfSections::load(|id| section_bytes(file, id.name(), ctx))?;
+    let dwarf = sections.borrow(|section| EndianSlice::new(section, endian));
+
+    // Line tables still describe functions that the linker removed, their addresses are set to 0 or another placeholder.
+    // Only counting code that ended up in a code section skips these.
+    let code: Vec<(u64, u64)> = file
+        .ram_root
+        .children
+        .iter()
+        .filter(|section| section.category() == SectionTypeCategory::Code)
+        .map(|section| (section.ram_bytes_start, section.ram_bytes_end))
+        .collect();
+    let in_code = |address: u64| {
+        code.iter()
+            .any(|(start, end)| (*start..*end).contains(&address))
+    };
+
+    let mut sizes: HashMap<String, u64> = HashMap::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit_offset = header.offset();
+        let unit = dwarf.unit(header)?;
+        let Some(program) = unit.line_program.clone() else {
+            continue;
+        };
+        // Paths of the files of this unit by their index, resolved on first use.
+        let mut paths: HashMap<u64, String> = HashMap::new();
+        // The row that the next row ends the address range of.
+        let mut previous: Option<(u64, u64)> = None;
+        let mut rows = program.rows();
+        while let Some((header, row)) = rows.next_row().with_context(|| {
+            format!("Failed to parse the line table of the unit at {unit_offset:?}")
+        })? {
+            if let Some((previous_address, file_index)) = previous.filter(|(a, _)| in_code(*a)) {
+                let path = paths.entry(file_index).or_insert_with(|| {
+                    file_path(&dwarf, &unit, header, file_index)
+                        .unwrap_or_else(|| format!("<unknown file {file_index}>"))
+                });
+                *sizes.entry(path.clone()).or_default() +=
+                    row.address().saturating_sub(previous_address);
+            }
+            previous = if row.end_sequence() {
+                None
+            } else {
+                Some((row.address(), row.file_index()))
+            };
+        }
+    }
+
+    let mut sizes: Vec<SourceFileSize> = sizes
+        .into_iter()
+        .map(|(path, bytes)| SourceFileSize { path, bytes })
+        .collect();
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    Ok(sizes)
+}
+
+type Reader<'a> = EndianSlice<'a, RunTimeEndian>;
+
+/// The path of file `index` of the line table `header`, joined to its directory unless it is absolute.
+fn file_path(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    header: &gimli::LineProgramHeader<Reader>,
+    index: u64,
+) -> Option<String> {
+    let file = header.file(index)?;
+    let attr_string = |attr| {
+        let string = dwarf.attr_string(unit, attr).ok()?;
+        Some(string.to_string_lossy().into_owned())
+    };
+    let name = attr_string(file.path_name())?;
+    let directory = file.directory(header).and_then(attr_string);
+    Some(match directory {
+        Some(directory) if !name.starts_with('/') && !directory.is_empty() => {
+            format!("{}/{name}", directory.trim_end_matches('/'))
+        }
+        _ => name,
+    })
+}
+
+/// The contents of the section named `name`, decompressed if needed, or no bytes if `file` has no such section.
+fn section_bytes<'a>(file: &'a ExecutableFile, name: &str, ctx: Ctx) -> Result<Cow<'a, [u8]>> {
+    let Some(section) = file.file_root.find_section(name) else {
+        return Ok(Cow::Borrowed(&[]));
+    };
+    let data = section
+        .file_data(&file.data)
+        .ok_or_else(|| anyhow!("{name} is out of bounds of the file"))?;
+    if is_compressed(section) {
+        decompress(data, ctx)
+            .map(Cow::Owned)
+            .with_context(|| format!("Failed to decompress {name}"))
+    } else {
+        Ok(Cow::Borrowed(data))
+    }
+}
+
+fn is_compressed(section: &FileNode) -> bool {
+    matches!(section.ty, SectionType::ElfSection { sh_flags, .. } if sh_flags & SHF_COMPRESSED as u64 != 0)
+}
+
+/// Decompresses the data of a `SHF_COMPRESSED` section, which starts with a [`CompressionHeader`].
+fn decompress(data: &[u8], ctx: Ctx) -> Result<Vec<u8>> {
+    let header = CompressionHeader::parse(data, 0, ctx)?;
+    if header.ch_type != ELFCOMPRESS_ZLIB {
+        return Err(anyhow!(
+            "Unsupported compression type {}, only zlib is supported",
+            header.ch_type
+        ));
+    }
+    let compressed = data
+        .get(CompressionHeader::size(ctx)..)
+        .ok_or_else(|| anyhow!("Compression header is truncated"))?;
+    let decompressed = miniz_oxide::inflate::decompress_to_vec_zlib(compressed)
+        .map_err(|err| anyhow!("{err:?}"))?;
+    if decompressed.len() as u64 != header.ch_size {
+        return Err(anyhow!(
+            "Decompressed to 0x{:x} bytes rather than 0x{:x}",
+            decompressed.len(),
+            header.ch_size
+        ));
+    }
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests are built with debug info, so the test binary's line tables include this file.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn attributes_code_to_this_file() {
+        let sizes = size_by_source_file(&ExecutableFile::load_self()).unwrap();
+        let this_file = sizes
+            .iter()
+            .find(|size| size.path.ends_with("src/dwarf.rs"))
+            .unwrap();
+        assert!(this_file.bytes > 0);
+    }
+}
@@ -0,0 +1,88 @@
+//! Shannon entropy of the bytes of a file, for spotting compressed, encrypted or packed regions.
+
+use std::{collections::HashMap, sync::Mutex};
+
+/// Number of bytes each entropy value in an [`EntropyProfile`] is computed over.
+pub const WINDOW: usize = 1024;
+
+/// The most entropy a byte can have, reached when every byte value is equally likely.
+pub const MAX_BITS_PER_BYTE: f32 = 8.0;
+
+/// The entropy of every [`WINDOW`] bytes of a file, and of the ranges of the file asked for with [`EntropyProfile::section`].
+///
+/// Code and structured data usually stay well below [`MAX_BITS_PER_BYTE`],
+/// while compressed or encrypted data comes close to it.
+pub struct EntropyProfile {
+    windows: Vec<f32>,
+    /// See [`EntropyProfile::section`].
+    sections: Mutex<HashMap<(u64, u64), f32>>,
+}
+
+impl EntropyProfile {
+    pub fn new(data: &[u8]) -> Self {
+        EntropyProfile {
+            windows: data.chunks(WINDOW).map(shannon_entropy).collect(),
+            sections: Default::default(),
+        }
+    }
+
+    /// The entropy of the bytes `start..end` taken as a whole, in bits per byte, computed on first use and then cached.
+    /// Unlike [`EntropyProfile::range`] it doesn't depend on where the range starts or how short it is.
+    /// `data` must be the file this profile was made from.
+    /// `None` if the range is empty or outside of the file.
+    pub fn section(&self, data: &[u8], start: u64, end: u64) -> Option<f32> {
+        let mut sections = self.sections.lock().unwrap();
+        if let Some(bits) = sections.get(&(start, end)) {
+            return Some(*bits);
+        }
+        let bytes = data
+            .get(start as usize..end as usize)
+            .filter(|bytes| !bytes.is_empty())?;
+        let bits = shannon_entropy(bytes);
+        sections.insert((start, end), bits);
+        Some(bits)
+    }
+
+    /// The average entropy of the windows overlapping the file range `start..end`, in bits per byte.
+    /// `None` if the range is empty or outside of the file.
+    pub fn range(&self, start: u64, end: u64) -> Option<f32> {
+        let first = (start / WINDOW as u64) as usize;
+        let last = (end.div_ceil(WINDOW as u64) as usize).min(self.windows.len());
+        let windows = self.windows.get(first..last).filter(|w| !w.is_empty())?;
+        Some(windows.iter().sum::<f32>() / windows.len() as f32)
+    }
+}
+
+/// The Shannon entropy of `bytes` in bits per byte, from 0 when all bytes are equal to [`MAX_BITS_PER_BYTE`].
+pub fn shannon_entropy(bytes: &[u8]) -> f32 {
+    let mut counts = [0u32; 256];
+    for byte in bytes {
+        counts[*byte as usize] += 1;
+    }
+    let len = bytes.len() as f32;
+    counts
+        .iter()
+        .filter(|count| **count != 0)
+        .map(|count| {
+            let p = *count as f32 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_entropy_covers_only_its_bytes() {
+        // A repetitive section within random looking bytes, shorter than a window.
+        let mut data: Vec<u8> = (0..WINDOW * 2).map(|i| (i * 167 % 256) as u8).collect();
+        data[100..200].fill(0x90);
+        let profile = EntropyProfile::new(&data);
+        assert_eq!(profile.section(&data, 100, 200), Some(0.0));
+        assert!(profile.range(100, 200).unwrap() > 6.0);
+        assert_eq!(profile.section(&data, 100, 100), None);
+        assert_eq!(profile.section(&data, 0, data.len() as u64 + 1), None);
+    }
+}
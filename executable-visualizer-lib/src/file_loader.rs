@@ -1,24 +1,82 @@
-use crate::sections::ExecutableFile;
-use anyhow::Result;
-use egui::Ui;
-use std::sync::mpsc;
-
-pub struct FileLoader {
-    rx: mpsc::Receiver<Result<ExecutableFile>>,
-    tx: mpsc::Sender<Result<ExecutableFile>>,
+use crate::sections::{find_embedded_executables, EmbeddedExecutable, ExecutableFile};
+use anyhow::{anyhow, Context, Result};
+use egui::{DroppedFile, Ui};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+/// How a file picked by the user is interpreted.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum LoadAs {
+    Executable,
+    /// See [`ExecutableFile::load_embedded`].
+    Embedded {
+        offset: u64,
+        len: Option<u64>,
+    },
+    /// See [`ExecutableFile::load_raw`].
+    Raw {
+        base_address: u64,
+    },
+}
+
+/// Sent back from a background task once it is done.
+struct Message {
+    cancel: Arc<AtomicBool>,
+    /// `None` when the user did not pick a file.
+    loaded: Option<Loaded>,
+}
+
+enum Loaded {
+    File(Result<Box<ExecutableFile>>),
+    Scan(Scan),
+    /// The result of [`FileLoader::save_file_from_user`], only errors are shown.
+    Saved(Result<()>),
+}
+
+/// The executables found within a file by [`find_embedded_executables`].
+struct Scan {
+    name: String,
+    data: Arc<[u8]>,
+    found: Vec<EmbeddedExecutable>,
+}
+
+pub(crate) struct FileLoader {
+    rx: mpsc::Receiver<Message>,
+    tx: mpsc::Sender<Message>,
     error: Option<String>,
+    /// The cancel flags of all loads that are still in progress, saves are not included.
+    in_progress: Vec<Arc<AtomicBool>>,
+    scan: Option<Scan>,
+    /// The directory of the last file opened from a dialog, where the next dialog starts.
+    /// Set by the background tasks once the file is loaded, always `None` on the web.
+    last_directory: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl Default for FileLoader {
     fn default() -> Self {
         let (tx, rx) = mpsc::channel();
         let error = None;
-        Self { rx, tx, error }
+        let in_progress = vec![];
+        let scan = None;
+        let last_directory = Default::default();
+        Self {
+            rx,
+            tx,
+            error,
+            in_progress,
+            scan,
+            last_directory,
+        }
     }
 }
 
 impl FileLoader {
-    pub fn display_error(&mut self, ui: &mut Ui) {
+    pub(crate) fn display_error(&mut self, ui: &mut Ui) {
         if let Some(error) = &self.error {
             let mut window_open = true;
             let screen_rect = ui.ctx().input(|i| i.screen_rect()).size();
@@ -35,35 +93,256 @@ impl FileLoader {
         }
     }
 
-    pub fn request_file_from_user(&self, ui: &mut Ui) {
-        let task = rfd::AsyncFileDialog::new().pick_file();
+    /// Lists the results of [`FileLoader::request_scan_from_user`], letting the user load each executable found.
+    pub(crate) fn display_scan(&mut self, ui: &mut Ui) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+
+        let mut window_open = true;
+        let mut load = None;
+        egui::Window::new(format!("Executables embedded in {}", scan.name))
+            .open(&mut window_open)
+            .vscroll(true)
+            .show(ui.ctx(), |ui| {
+                if scan.found.is_empty() {
+                    ui.label("No executables found.");
+                }
+                egui::Grid::new("embedded_executables").show(ui, |ui| {
+                    for found in &scan.found {
+                        ui.monospace(format!("{:?}", found.format));
+                        ui.monospace(format!("0x{:x}", found.offset));
+                        if ui.button("Load").clicked() {
+                            load = Some(found.offset);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if let Some(offset) = load {
+            let name = format!("{} @ 0x{offset:x}", scan.name);
+            let data = scan.data.clone();
+            self.spawn_load(ui, move |cancel| async move {
+                Some(Loaded::File(
+                    ExecutableFile::load_embedded_cancellable(name, &data, offset, None, &cancel)
+                        .map(Box::new),
+                ))
+            });
+        }
+        if !window_open {
+            self.scan = None;
+        }
+    }
+
+    pub(crate) fn show_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Ask the user for a file and load it in the background, interpreting it according to `load_as`.
+    pub(crate) fn request_file_from_user(&mut self, ui: &mut Ui, load_as: LoadAs) {
+        let task = self.file_dialog().pick_file();
+        let last_directory = self.last_directory.clone();
+        self.spawn_load(ui, move |cancel| async move {
+            let handle = task.await?;
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let name = handle.file_name();
+            let contents = handle.read().await;
+            let file = match load_as {
+                LoadAs::Executable => {
+                    ExecutableFile::load_from_bytes_cancellable(name, &contents, &cancel)
+                }
+                LoadAs::Embedded { offset, len } => {
+                    ExecutableFile::load_embedded_cancellable(name, &contents, offset, len, &cancel)
+                }
+                LoadAs::Raw { base_address } => {
+                    ExecutableFile::load_raw(name, &contents, base_address)
+                }
+            };
+            if file.is_ok() {
+                remember_directory(&last_directory, &handle);
+            }
+            Some(Loaded::File(file.map(Box::new)))
+        });
+    }
+
+    /// Load files dropped onto the window in the background.
+    /// On native the files are read from their path, on the web their contents come with them.
+    pub(crate) fn load_dropped_files(&mut self, ui: &mut Ui, dropped_files: Vec<DroppedFile>) {
+        for dropped in dropped_files {
+            self.spawn_load(ui, move |cancel| async move {
+                let name = match &dropped.path {
+                    Some(path) => path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.display().to_string()),
+                    None => dropped.name.clone(),
+                };
+                let file = match (&dropped.bytes, &dropped.path) {
+                    (Some(bytes), _) => {
+                        ExecutableFile::load_from_bytes_cancellable(name, bytes, &cancel)
+                    }
+                    (None, Some(path)) => std::fs::read(path)
+                        .with_context(|| format!("Failed to read {}", path.display()))
+                        .and_then(|bytes| {
+                            ExecutableFile::load_from_bytes_cancellable(name, &bytes, &cancel)
+                        }),
+                    (None, None) => Err(anyhow!(
+                        "The contents of dropped file {name} are not available"
+                    )),
+                };
+                Some(Loaded::File(file.map(Box::new)))
+            });
+        }
+    }
+
+    /// Ask the user for a file and search it for embedded executables in the background.
+    pub(crate) fn request_scan_from_user(&mut self, ui: &mut Ui) {
+        let task = self.file_dialog().pick_file();
+        let last_directory = self.last_directory.clone();
+        self.spawn_load(ui, move |cancel| async move {
+            let file = task.await?;
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            remember_directory(&last_directory, &file);
+            let name = file.file_name();
+            let data: Arc<[u8]> = file.read().await.into();
+            let found = find_embedded_executables(&data);
+            Some(Loaded::Scan(Scan { name, data, found }))
+        });
+    }
+
+    /// Ask the user where to save `contents`, suggesting `file_name`, and write it there in the background.
+    /// On the web the browser decides where the file is downloaded to instead.
+    pub(crate) fn save_file_from_user(&mut self, ui: &mut Ui, file_name: &str, contents: Vec<u8>) {
+        let task = self.file_dialog().set_file_name(file_name).save_file();
+        // Not a load, so it isn't shown as loading and can't be cancelled.
+        self.spawn(ui, Arc::default(), move |_| async move {
+            let file = task.await?;
+            let saved = file
+                .write(&contents)
+                .await
+                .with_context(|| format!("Failed to save {}", file.file_name()));
+            Some(Loaded::Saved(saved))
+        });
+    }
+
+    /// A dialog starting in the directory of the last file opened, if any.
+    fn file_dialog(&self) -> rfd::AsyncFileDialog {
+        let dialog = rfd::AsyncFileDialog::new();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(directory) = self.last_directory.lock().unwrap().clone() {
+            return dialog.set_directory(directory);
+        }
+        dialog
+    }
+
+    /// Runs the load `task` in the background, counted by [`FileLoader::is_loading`] until it is done
+    /// and abandoned by [`FileLoader::cancel`].
+    fn spawn_load<F, Fut>(&mut self, ui: &Ui, task: F)
+    where
+        F: FnOnce(Arc<AtomicBool>) -> Fut + 'static,
+        Fut: std::future::Future<Output = Option<Loaded>> + MaybeSend + 'static,
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.in_progress.push(cancel.clone());
+        self.spawn(ui, cancel, task);
+    }
+
+    /// Runs `task` in the background, sending its result back to [`FileLoader::recive_files_from_user`].
+    /// The result is dropped if `cancel` is set by then.
+    fn spawn<F, Fut>(&mut self, ui: &Ui, cancel: Arc<AtomicBool>, task: F)
+    where
+        F: FnOnce(Arc<AtomicBool>) -> Fut + 'static,
+        Fut: std::future::Future<Output = Option<Loaded>> + MaybeSend + 'static,
+    {
         let ctx = ui.ctx().clone();
         let sender = self.tx.clone();
+        let future = task(cancel.clone());
         execute(async move {
-            let file = task.await;
-            if let Some(file) = file {
-                let name = file.file_name();
-                let contents = file.read().await;
-                sender
-                    .send(ExecutableFile::load_from_bytes(name, &contents))
-                    .ok();
-                ctx.request_repaint();
-            }
+            let loaded = future.await;
+            sender.send(Message { cancel, loaded }).ok();
+            ctx.request_repaint();
         });
     }
 
-    pub fn recive_file_from_user(&mut self) -> Option<ExecutableFile> {
-        match self.rx.try_recv().ok() {
-            Some(Ok(file)) => Some(file),
-            Some(Err(err)) => {
-                self.error = Some(format!("{err:?}"));
-                None
+    /// Returns true while any requested file has not finished loading.
+    pub(crate) fn is_loading(&self) -> bool {
+        !self.in_progress.is_empty()
+    }
+
+    /// How many requested files have not finished loading, including dialogs the user has not answered yet.
+    pub(crate) fn loading_count(&self) -> usize {
+        self.in_progress.len()
+    }
+
+    /// Abandon all loads in progress.
+    pub(crate) fn cancel(&mut self) {
+        for cancel in self.in_progress.drain(..) {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns every file that finished loading since the last call.
+    pub(crate) fn recive_files_from_user(&mut self) -> Vec<ExecutableFile> {
+        let mut files = vec![];
+        while let Ok(message) = self.rx.try_recv() {
+            self.in_progress
+                .retain(|cancel| !Arc::ptr_eq(cancel, &message.cancel));
+            if message.cancel.load(Ordering::Relaxed) {
+                // The user no longer cares about this file, not even if it failed to load.
+                continue;
+            }
+            match message.loaded {
+                Some(Loaded::File(Ok(file))) => files.push(*file),
+                Some(Loaded::File(Err(err))) => self.add_error(format!("{err:?}")),
+                Some(Loaded::Scan(scan)) => self.scan = Some(scan),
+                Some(Loaded::Saved(Err(err))) => self.add_error(format!("{err:?}")),
+                Some(Loaded::Saved(Ok(()))) => {}
+                None => {}
             }
-            None => None,
+        }
+        files
+    }
+
+    /// Keeps earlier errors that have not been dismissed yet, so a batch of failed loads are all reported.
+    fn add_error(&mut self, error: String) {
+        match &mut self.error {
+            Some(existing) => {
+                existing.push_str("\n\n");
+                existing.push_str(&error);
+            }
+            None => self.error = Some(error),
         }
     }
 }
 
+/// Futures run by [`execute`] need to be `Send` on native where they run on another thread.
+#[cfg(not(target_arch = "wasm32"))]
+trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+#[cfg(target_arch = "wasm32")]
+trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Remembers the directory of `file` for [`FileLoader::file_dialog`].
+#[cfg(not(target_arch = "wasm32"))]
+fn remember_directory(last_directory: &Mutex<Option<PathBuf>>, file: &rfd::FileHandle) {
+    if let Some(directory) = file.path().parent() {
+        *last_directory.lock().unwrap() = Some(directory.to_owned());
+    }
+}
+
+/// The browser picks the directory of its file dialogs itself.
+#[cfg(target_arch = "wasm32")]
+fn remember_directory(_last_directory: &Mutex<Option<PathBuf>>, _file: &rfd::FileHandle) {}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn execute<F: std::future::Future<Output = ()> + Send + 'static>(f: F) {
     std::thread::spawn(move || futures::executor::block_on(f));
@@ -1,15 +1,70 @@
-use crate::sections::{ExecutableFile, FileNode};
+//! An egui widget for drawing the layout of [`ExecutableFile`]s.
+//!
+//! Call [`ui`] every frame with the files to draw and an [`Options`] that persists between frames.
+
+use crate::{
+    diff::{Baseline, NodeDiff},
+    entropy::{EntropyProfile, MAX_BITS_PER_BYTE, WINDOW},
+    sections::{ExecutableFile, FileNode, Layout, SectionLink, SectionType, SectionTypeCategory},
+};
+use anyhow::{anyhow, Result};
 use egui::*;
-use std::vec;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    vec,
+};
+
+mod histogram;
+mod treemap;
 
 const HOVER_COLOR: Rgba = Rgba::from_rgb(0.8, 0.8, 0.8);
+/// How much of the text color is mixed in behind the file under the cursor.
+const HOVERED_FILE_OPACITY: f32 = 0.05;
+/// How much of the canvas color is drawn behind the timeline labels when they are pinned over the scopes.
+const PINNED_TIMELINE_OPACITY: f32 = 0.8;
+/// Scopes that don't match [`Options::type_filter`] are drawn with this alpha.
+const FILTERED_ALPHA: f32 = 0.15;
+/// Arrows from the selected scope to the sections it links to.
+const LINK_COLOR: Color32 = Color32::from_rgb(255, 200, 0);
+/// Parts of the file not described by any section, see [`SectionType::Gap`].
+const GAP_COLOR: Rgba = Rgba::from_rgb(0.15, 0.15, 0.15);
+/// Sections the baseline has but a file compared against it doesn't, only listed in the summary as there is nothing to draw.
+const REMOVED_COLOR: Color32 = Color32::from_rgb(200, 80, 200);
+/// Marks where execution starts in the RAM layout, see [`ExecutableFile::entry_point`].
+const ENTRY_POINT_COLOR: Color32 = Color32::from_rgb(80, 220, 80);
+/// Outlines scopes that share bytes of the file with a sibling, see [`FileNode::overlaps_sibling`].
+const OVERLAP_COLOR: Color32 = Color32::from_rgb(255, 60, 40);
+/// Width in points of each column of a scope drawn as an entropy strip, see [`ColorBy::Entropy`].
+const ENTROPY_STRIP_STEP: f32 = 2.0;
+/// Height of the strip drawn by [`minimap_ui`].
+const MINIMAP_HEIGHT: f32 = 24.0;
+/// Brightens the part of the minimap that is in view.
+const MINIMAP_VIEWPORT_FILL: Color32 = Color32::from_rgba_premultiplied(40, 40, 40, 40);
+/// Clicking within this many points of the start of a section with children toggles collapsing it.
+const DISCLOSURE_WIDTH: f32 = 20.0;
+/// [`Options::max_depth`] when first turned on.
+const DEFAULT_MAX_DEPTH: usize = 4;
+/// The deepest [`Options::max_depth`] the slider goes to, trees are rarely deeper.
+const MAX_DEPTH_SLIDER: usize = 16;
+/// Zooming in stops once this many bytes span the width of the canvas.
+const MIN_CANVAS_WIDTH_BYTES: f32 = 1.0;
+/// Zooming out stops once the canvas spans this many times the widest file.
+const MAX_ZOOM_OUT: f32 = 10.0;
 type BytesCount = u64;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum SortBy {
+    /// Scopes at their offsets in the file or in memory.
     #[default]
     Actual,
+    /// The sections below the first scope merged by name, whichever scope they are in:
+    /// a row with a block per name the size of its sections combined, the largest first,
+    /// and under each block its sections, the largest first.
+    /// So e.g. the `.text` sections of all the members of an archive form one block.
+    /// Positions on the timeline are then cumulative sizes rather than offsets.
     GroupedForSpaceUsageAnalysis,
 }
 
@@ -37,6 +92,138 @@ impl Sorting {
     }
 }
 
+/// Where the timeline labels count bytes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TimelineOrigin {
+    /// Offsets from the start of the file.
+    #[default]
+    Absolute,
+    /// Offsets from the start of [`Options::selected`], useful for measuring distances within a region.
+    Selection,
+}
+
+/// How the size of a scope maps to its color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SizeScale {
+    #[default]
+    Linear,
+    /// Sizes that differ by the same factor differ by the same amount of color,
+    /// so small scopes are not all the same color when a few scopes are much larger.
+    Logarithmic,
+}
+
+/// What the color of a scope shows, unless it is compared to a baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ColorBy {
+    /// A gradient from small to large, see [`Options::size_scale`].
+    #[default]
+    Size,
+    /// A hue per [`SectionTypeCategory`], e.g. all debug info shares one color.
+    Type,
+    /// From blue for repetitive bytes to red for random looking bytes, see [`ExecutableFile::entropy`].
+    /// Scopes wide enough are drawn as a strip showing how the entropy changes along them.
+    Entropy,
+}
+
+/// What clicking on a scope does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ClickAction {
+    /// Click to select and zoom to a scope, double-click to focus on it.
+    #[default]
+    Zoom,
+    /// Click to select a scope and show its details without moving the view, double-click to zoom to it.
+    Select,
+}
+
+/// How the scopes of the files are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Visualization {
+    /// Scopes laid out along a byte axis that can be panned and zoomed, children in rows below their parent.
+    #[default]
+    Timeline,
+    /// Scopes as tiles with areas proportional to their sizes, children tiled within their parent.
+    /// Small sections stay large enough to click, but positions in the file are lost.
+    Treemap,
+}
+
+/// The `egui` visuals used while the inspector is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Theme {
+    #[default]
+    Dark,
+    /// Easier to read in bright surroundings.
+    Light,
+}
+
+impl Theme {
+    fn visuals(self) -> Visuals {
+        match self {
+            Theme::Dark => Visuals::dark(),
+            Theme::Light => Visuals::light(),
+        }
+    }
+}
+
+/// Which of the trees of each file are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum LayoutView {
+    /// The file layout above the RAM layout.
+    #[default]
+    Both,
+    File,
+    Ram,
+}
+
+impl LayoutView {
+    fn shows(self, layout: Layout) -> bool {
+        match self {
+            LayoutView::Both => true,
+            LayoutView::File => layout == Layout::File,
+            LayoutView::Ram => layout == Layout::Ram,
+        }
+    }
+
+    /// The view the `V` key switches to, flipping between the file and RAM layouts.
+    fn toggled(self) -> Self {
+        match self {
+            LayoutView::Both | LayoutView::Ram => LayoutView::File,
+            LayoutView::File => LayoutView::Ram,
+        }
+    }
+}
+
+/// Identifies a [`FileNode`] within the files passed to [`ui`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodePath {
+    /// Index of the file in the files passed to [`ui`]
+    pub file: usize,
+    /// Which tree of the file the node is in
+    pub layout: Layout,
+    /// Indexes into `children` leading from the root of the tree to the node
+    pub children: Vec<usize>,
+}
+
+impl NodePath {
+    /// The node this path refers to, if it still exists.
+    pub fn resolve<'a>(&self, files: &'a [ExecutableFile]) -> Option<&'a FileNode> {
+        files
+            .get(self.file)?
+            .root(self.layout)
+            .descendant(&self.children)
+    }
+
+    fn is(&self, file: &ScopeFile, children: &[usize]) -> bool {
+        self.file == file.index && self.layout == file.layout && self.children == children
+    }
+}
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
@@ -60,6 +247,10 @@ pub struct Options {
     pub spacing: f32,
     pub rounding: f32,
 
+    /// Only draw this many rows of each tree, the children of scopes in the last row are hidden.
+    /// Rows are counted from the root, or from the focused scope when focused.
+    pub max_depth: Option<usize>,
+
     pub frame_list_height: f32,
     /// Distance between subsequent frames in the frame view.
     pub frame_width: f32,
@@ -68,10 +259,118 @@ pub struct Options {
 
     pub to_scale: bool,
 
+    /// Append the size of a section to its label when there is room for it.
+    pub label_sizes: bool,
+
+    /// Write offsets and addresses in hexadecimal rather than decimal.
+    pub hex_offsets: bool,
+
+    /// Write sizes in KiB and MiB of 1024 bytes rather than KB and MB of 1000 bytes.
+    pub binary_units: bool,
+
+    pub color_by: ColorBy,
+
+    /// Only used when coloring by size.
+    pub size_scale: SizeScale,
+
+    /// Shift the colors of each file after the first towards a hue of its own, so stacked files are easy to tell apart.
+    pub tint_files: bool,
+
+    /// When a file is loaded, zoom to the first section with this name.
+    /// Does nothing when empty.
+    pub initial_zoom_section: String,
+
+    /// Scopes in these categories are drawn normally, the rest are dimmed.
+    pub type_filter: HashSet<SectionTypeCategory>,
+
+    /// Skip scopes filtered out by [`Self::type_filter`] instead of dimming them,
+    /// unless they contain scopes that pass the filter.
+    pub hide_filtered: bool,
+
+    /// When not empty, scopes whose name doesn't contain this are dimmed, ignoring case.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub search: String,
+
+    /// Where the timeline labels count bytes from.
+    pub timeline_origin: TimelineOrigin,
+
+    /// Keep the timeline labels at the top and bottom of the visible area while scrolling vertically.
+    pub pin_timeline: bool,
+
+    pub click_action: ClickAction,
+
+    /// Ignored while a scope is focused, the layout of the focused scope is drawn instead.
+    pub layout_view: LayoutView,
+
+    pub visualization: Visualization,
+
+    pub theme: Theme,
+
+    /// The scope the user last clicked on.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub selected: Option<NodePath>,
+
+    /// When set only this scope and its children are drawn, as if it were the root of its file.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub focused: Option<NodePath>,
+
+    /// The file other files are compared against.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub baseline: Option<Baseline>,
+
+    /// Color scopes by how they changed compared to [`Self::baseline`] instead of by size.
+    pub color_by_baseline: bool,
+
+    /// Draw arrows from [`Self::selected`] to the sections it refers to, see [`FileNode::links`].
+    pub show_links: bool,
+
+    /// Show a strip of the whole width of the files above the timeline, with a box around the part in view.
+    pub show_minimap: bool,
+
+    /// Show the bytes of [`Self::selected`] in a panel beside the scopes.
+    pub show_hex_dump: bool,
+
+    /// List the printable strings in [`Self::selected`] in a panel beside the scopes.
+    pub show_strings: bool,
+
+    /// Strings with fewer characters than this are not listed, see [`Self::show_strings`].
+    pub min_string_len: usize,
+
+    /// Show a histogram of the sizes of the sections and the largest sections in a panel beside the scopes.
+    pub show_histogram: bool,
+
     /// Set when user clicks a scope.
     /// First part is `now()`, second is range.
     #[cfg_attr(feature = "serde", serde(skip))]
     zoom_to_relative_bytes_range: Option<(f64, (BytesCount, BytesCount))>,
+
+    /// Where the header of each drawn file was painted last frame, for starting to drag it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    file_headers: Vec<(usize, Rect)>,
+
+    /// Index of the file whose header is being dragged to move it up or down the list.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dragged_file: Option<usize>,
+
+    /// The scope the context menu was opened on, `None` when it was opened on the background.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    context_menu_scope: Option<NodePath>,
+
+    /// Where the scopes were drawn last frame, see [`Options::canvas_rect`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    canvas_rect: Option<Rect>,
+
+    /// What [`Self::show_histogram`] shows, kept until the files change.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    histogram_leaves: histogram::Leaves,
+
+    /// See [`Options::size_range`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    size_range: Option<(FilesKey, SizeRange)>,
+
+    /// See [`name_groups`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    name_groups: NameGroups,
 }
 
 impl Default for Options {
@@ -87,15 +386,218 @@ impl Default for Options {
             rect_height: 16.0,
             spacing: 4.0,
             rounding: 4.0,
+            max_depth: None,
 
             frame_list_height: 48.0,
             frame_width: 10.0,
 
             sorting: Default::default(),
             to_scale: true,
+            label_sizes: false,
+            hex_offsets: true,
+            binary_units: false,
+            color_by: ColorBy::Size,
+            size_scale: SizeScale::Linear,
+            tint_files: true,
+            initial_zoom_section: String::new(),
+            type_filter: SectionTypeCategory::ALL.into_iter().collect(),
+            hide_filtered: false,
+            search: String::new(),
+            timeline_origin: TimelineOrigin::Absolute,
+            pin_timeline: true,
+            click_action: ClickAction::Zoom,
+            layout_view: LayoutView::Both,
+            visualization: Visualization::Timeline,
+            theme: Theme::Dark,
+            selected: None,
+            focused: None,
+            baseline: None,
+            color_by_baseline: false,
+            show_links: false,
+            show_minimap: true,
+            show_hex_dump: false,
+            show_strings: false,
+            min_string_len: 4,
+            show_histogram: false,
 
             zoom_to_relative_bytes_range: None,
+            file_headers: vec![],
+            dragged_file: None,
+            context_menu_scope: None,
+            canvas_rect: None,
+            histogram_leaves: Default::default(),
+            size_range: None,
+            name_groups: Default::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Describes the flags accepted by [`Options::from_args`], for including in a `--help` message.
+    pub const ARGS_HELP: &'static str =
+        "  --to-scale=BOOL          Draw scopes to scale (default true)
+  --sort=actual|grouped    Order of the scopes (default actual)
+  --color-by=size|type|entropy
+                           What colors show (default size)
+  --size-scale=linear|log  How sizes map to colors (default linear)
+  --label-sizes=BOOL       Append sizes to labels (default false)
+  --hex-offsets=BOOL       Write offsets in hexadecimal (default true)
+  --binary-units=BOOL      Write sizes in KiB rather than KB (default false)
+  --tint-files=BOOL        Give each file its own hue (default true)
+  --view=both|file|ram     Which layouts to draw (default both)
+  --visualization=timeline|treemap
+                           How scopes are drawn (default timeline)
+  --theme=dark|light       Colors of the whole window (default dark)
+  --zoom-to=SECTION        Zoom to this section when a file is loaded";
+
+    /// Options configured by command line flags like `--to-scale=false`, see [`Options::ARGS_HELP`].
+    /// Anything not set by a flag keeps the same default as in the GUI.
+    pub fn from_args<S: AsRef<str>>(args: impl IntoIterator<Item = S>) -> Result<Self> {
+        let mut options = Options::default();
+        for arg in args {
+            let arg = arg.as_ref();
+            let (flag, value) = arg
+                .strip_prefix("--")
+                .and_then(|arg| arg.split_once('='))
+                .ok_or_else(|| anyhow!("Expected an argument like --flag=value but got {arg:?}"))?;
+            let bool_value = || match value {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(anyhow!("--{flag} must be true or false, not {value:?}")),
+            };
+            let invalid = || anyhow!("Unsupported value {value:?} for --{flag}");
+            match flag {
+                "to-scale" => options.to_scale = bool_value()?,
+                "sort" => {
+                    options.sorting.sort_by = match value {
+                        "actual" => SortBy::Actual,
+                        "grouped" => SortBy::GroupedForSpaceUsageAnalysis,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "color-by" => {
+                    options.color_by = match value {
+                        "size" => ColorBy::Size,
+                        "type" => ColorBy::Type,
+                        "entropy" => ColorBy::Entropy,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "size-scale" => {
+                    options.size_scale = match value {
+                        "linear" => SizeScale::Linear,
+                        "log" => SizeScale::Logarithmic,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "label-sizes" => options.label_sizes = bool_value()?,
+                "hex-offsets" => options.hex_offsets = bool_value()?,
+                "binary-units" => options.binary_units = bool_value()?,
+                "tint-files" => options.tint_files = bool_value()?,
+                "view" => {
+                    options.layout_view = match value {
+                        "both" => LayoutView::Both,
+                        "file" => LayoutView::File,
+                        "ram" => LayoutView::Ram,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "visualization" => {
+                    options.visualization = match value {
+                        "timeline" => Visualization::Timeline,
+                        "treemap" => Visualization::Treemap,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "theme" => {
+                    options.theme = match value {
+                        "dark" => Theme::Dark,
+                        "light" => Theme::Light,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "zoom-to" => options.initial_zoom_section = value.to_owned(),
+                _ => return Err(anyhow!("Unknown option --{flag}")),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Where the scopes and the timeline were drawn in the last call to [`ui`], in points.
+    /// `None` before the first call.
+    pub fn canvas_rect(&self) -> Option<Rect> {
+        self.canvas_rect
+    }
+
+    /// Compare `section` against [`Self::baseline`], `None` if there is no baseline or `file` is the baseline.
+    fn compare_to_baseline(&self, file: &ScopeFile, section: &FileNode) -> Option<NodeDiff> {
+        let baseline = self.baseline.as_ref().filter(|_| !file.is_baseline)?;
+        Some(baseline.compare(file.layout, section, file.data))
+    }
+
+    /// The [`SizeRange`] of `files`, recomputed only after a file is loaded, closed or moved.
+    fn size_range(&mut self, files: &[ExecutableFile]) -> SizeRange {
+        let key = FilesKey::of(files);
+        match &self.size_range {
+            Some((cached_key, range)) if *cached_key == key => *range,
+            _ => {
+                let range = SizeRange::of(files);
+                self.size_range = Some((key, range));
+                range
+            }
+        }
+    }
+
+    /// Zoom to the section named [`Self::initial_zoom_section`] in a newly loaded `file`.
+    /// The section is looked up in the layout shown by [`Self::layout_view`], the file layout when both are shown.
+    /// `time` is the current `egui` time, pass `0.0` to skip the zoom animation.
+    pub fn zoom_to_initial_section(&mut self, file: &ExecutableFile, time: f64) {
+        if self.initial_zoom_section.is_empty() {
+            return;
         }
+        let layout = match self.layout_view {
+            LayoutView::Ram => Layout::Ram,
+            LayoutView::File | LayoutView::Both => Layout::File,
+        };
+        if let Some(section) = file.root(layout).find_section(&self.initial_zoom_section) {
+            self.zoom_to_relative_bytes_range =
+                Some((time, (section.bytes_start, section.bytes_end)));
+        }
+    }
+}
+
+/// The file that the scopes being painted belong to.
+struct ScopeFile<'a> {
+    index: usize,
+    layout: Layout,
+    data: &'a [u8],
+    /// Length of the root node of `layout`.
+    len: u64,
+    /// True if this is the file pinned as [`Options::baseline`].
+    is_baseline: bool,
+    /// See [`ExecutableFile::entry_point`].
+    entry_point: Option<u64>,
+    /// Only set when coloring by [`ColorBy::Entropy`], as it takes a pass over the whole file to compute.
+    entropy: Option<&'a EntropyProfile>,
+}
+
+impl ScopeFile<'_> {
+    /// The entry point if `section` is loaded at addresses including it.
+    fn entry_point_in(&self, section: &FileNode) -> Option<u64> {
+        self.entry_point.filter(|entry_point| {
+            !matches!(section.ty, SectionType::Root)
+                && (section.ram_bytes_start..section.ram_bytes_end).contains(entry_point)
+        })
+    }
+
+    /// The entropy of the file bytes of `section`, 0 without [`Self::entropy`] or for sections not in the file like `.bss`.
+    fn section_entropy(&self, section: &FileNode) -> f32 {
+        self.entropy
+            .filter(|_| section.category() != SectionTypeCategory::Bss)
+            .and_then(|entropy| {
+                entropy.section(self.data, section.file_bytes_start, section.file_bytes_end)
+            })
+            .unwrap_or(0.0)
     }
 }
 
@@ -114,6 +616,175 @@ struct Info {
     stop_bytes: BytesCount,
 
     font_id: FontId,
+
+    /// For text and lines painted straight onto the canvas.
+    text_color: Color32,
+    /// What the canvas is filled with, for picking text colors that stand out against translucent scopes.
+    canvas_color: Color32,
+
+    /// Union of everything painted so far, clipped to the canvas.
+    /// Used to size the `ScrollArea` to the content.
+    painted_rect: Cell<Rect>,
+
+    /// Sizes of the scopes in the loaded files, the size coloring spans this range.
+    size_range: SizeRange,
+
+    /// Everything painted this frame that should be exposed to screen readers.
+    accessible: RefCell<Vec<AccessibleItem>>,
+
+    /// Set when [`Options::show_links`] is on and the selected scope links to other sections.
+    links: Option<SelectedLinks>,
+
+    /// [`Options::search`] in lowercase.
+    search: String,
+}
+
+/// Where the selected scope and the sections it links to were painted this frame.
+struct SelectedLinks {
+    file: usize,
+    layout: Layout,
+    links: Vec<SectionLink>,
+    selected_rect: Cell<Option<Rect>>,
+    /// The painted linked sections, along with [`SectionLink::relation`].
+    target_rects: RefCell<Vec<(Rect, &'static str)>>,
+}
+
+impl SelectedLinks {
+    fn new(options: &Options, files: &[ExecutableFile]) -> Option<Self> {
+        if !options.show_links {
+            return None;
+        }
+        let selected = options.selected.as_ref()?;
+        let links = selected.resolve(files)?.links.clone();
+        if links.is_empty() {
+            return None;
+        }
+        Some(SelectedLinks {
+            file: selected.file,
+            layout: selected.layout,
+            links,
+            selected_rect: Cell::new(None),
+            target_rects: RefCell::new(vec![]),
+        })
+    }
+
+    /// Remember where `section` was painted if it is a target of the selected links.
+    fn mark_painted(&self, file: &ScopeFile, section: &FileNode, rect: Rect) {
+        if file.index != self.file || file.layout != self.layout {
+            return;
+        }
+        for link in &self.links {
+            if link.name == section.name && link.file_bytes_start == section.file_bytes_start {
+                self.target_rects.borrow_mut().push((rect, link.relation));
+            }
+        }
+    }
+
+    /// An arc below the scopes from the selected scope to each painted target, labeled with what the target is.
+    fn paint(&self, info: &Info) {
+        let Some(from) = self.selected_rect.get() else {
+            return;
+        };
+        let stroke = Stroke::new(2.0, LINK_COLOR);
+        for &(to, relation) in self.target_rects.borrow().iter() {
+            let start = from.center_bottom();
+            let end = to.center_bottom();
+            let sag = 20.0 + (end.x - start.x).abs() * 0.1;
+            let control = pos2((start.x + end.x) / 2.0, start.y.max(end.y) + sag);
+            info.painter
+                .add(epaint::QuadraticBezierShape::from_points_stroke(
+                    [start, control, end],
+                    false,
+                    Color32::TRANSPARENT,
+                    stroke,
+                ));
+
+            // The curve ends heading from the control point towards `end`.
+            let dir = (end - control).normalized();
+            let side = dir.rot90() * 4.0;
+            let back = end - dir * 8.0;
+            info.painter.line_segment([back + side, end], stroke);
+            info.painter.line_segment([back - side, end], stroke);
+
+            let middle = start.lerp(control, 0.5).lerp(control.lerp(end, 0.5), 0.5);
+            info.painter.text(
+                middle,
+                Align2::CENTER_TOP,
+                relation,
+                info.font_id.clone(),
+                LINK_COLOR,
+            );
+        }
+    }
+}
+
+/// A painted item that is turned into a widget so that AccessKit can describe it to assistive technology.
+struct AccessibleItem {
+    id: Id,
+    rect: Rect,
+    info: WidgetInfo,
+}
+
+/// Identifies the loaded files and their order, so what is computed from them can be kept
+/// until a file is loaded, closed or moved.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct FilesKey(Vec<(usize, usize)>);
+
+impl FilesKey {
+    fn of(files: &[ExecutableFile]) -> Self {
+        FilesKey(
+            files
+                .iter()
+                .map(|file| (file.data.as_ptr() as usize, file.data.len()))
+                .collect(),
+        )
+    }
+}
+
+/// The smallest and largest scopes below the roots of a set of files.
+#[derive(Clone, Copy, Debug)]
+struct SizeRange {
+    min: BytesCount,
+    max: BytesCount,
+}
+
+impl SizeRange {
+    fn of(files: &[ExecutableFile]) -> Self {
+        fn visit(node: &FileNode, range: &mut SizeRange) {
+            for child in &node.children {
+                range.min = range.min.min(child.len());
+                range.max = range.max.max(child.len());
+                visit(child, range);
+            }
+        }
+
+        let mut range = SizeRange {
+            min: BytesCount::MAX,
+            max: 0,
+        };
+        for file in files {
+            visit(&file.file_root, &mut range);
+            visit(&file.ram_root, &mut range);
+        }
+        if range.min > range.max {
+            range.min = 0;
+        }
+        range
+    }
+
+    /// Where `bytes` lies in the range, from 0.0 for the smallest scope to 1.0 for the largest.
+    fn fraction(&self, bytes: BytesCount, scale: SizeScale) -> f32 {
+        if self.max <= self.min {
+            return 1.0;
+        }
+        let map = |bytes: BytesCount| match scale {
+            SizeScale::Linear => bytes as f64,
+            // Offset by one so empty scopes don't map to negative infinity.
+            SizeScale::Logarithmic => (bytes as f64 + 1.0).ln(),
+        };
+        let bytes = bytes.clamp(self.min, self.max);
+        ((map(bytes) - map(self.min)) / (map(self.max) - map(self.min))) as f32
+    }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -124,6 +795,29 @@ enum PaintResult {
 }
 
 impl Info {
+    fn mark_accessible(&self, id: Id, rect: Rect, info: WidgetInfo) {
+        let rect = rect.intersect(self.canvas);
+        if rect.is_positive() {
+            self.accessible
+                .borrow_mut()
+                .push(AccessibleItem { id, rect, info });
+        }
+    }
+
+    fn mark_painted(&self, rect: Rect) {
+        let rect = rect.intersect(self.canvas);
+        if rect.is_positive() {
+            self.painted_rect.set(self.painted_rect.get().union(rect));
+        }
+    }
+
+    fn bytes_from_point(&self, options: &Options, x: f32) -> f64 {
+        self.start_bytes as f64
+            + (x - self.canvas.min.x - options.sideways_pan_in_points) as f64
+                * options.canvas_width_bytes as f64
+                / self.canvas.width() as f64
+    }
+
     fn point_from_bytes(&self, options: &Options, ns: BytesCount) -> f32 {
         self.canvas.min.x
             + options.sideways_pan_in_points
@@ -132,21 +826,255 @@ impl Info {
 }
 
 /// Show the Inspector.
-pub fn ui(ui: &mut egui::Ui, options: &mut Options, files: &mut [ExecutableFile]) {
+/// Files can be closed from their header, which removes them from `files`.
+pub fn ui(ui: &mut egui::Ui, options: &mut Options, files: &mut Vec<ExecutableFile>) {
+    if ui.visuals().dark_mode != (options.theme == Theme::Dark) {
+        // Also applied to this `ui`, the rest of the window picks it up next frame.
+        ui.ctx().set_visuals(options.theme.visuals());
+        *ui.visuals_mut() = options.theme.visuals();
+    }
+
     ui.horizontal(|ui| {
         ui.vertical(|ui| {
-            ui.horizontal(|ui| {
-                ui.colored_label(ui.visuals().widgets.inactive.text_color(), "❓")
-                    .on_hover_text(
-                        "Drag to pan.\n\
+            // Wrapped so every control can be reached in a narrow window.
+            ui.horizontal_wrapped(|ui| {
+                let help = "Drag to pan.\n\
             Zoom: Ctrl/cmd + scroll, or drag with secondary mouse button.\n\
-            Click on a scope to zoom to it.\n\
-            Double-click to reset view.",
+            Click on a scope to select and zoom to it, or only select it with \"click to: select\".\n\
+            Double-click on a scope to focus on it, or zoom to it with \"click to: select\". Press Escape to leave focus.\n\
+            Click the ⏷ of a scope to collapse its children.\n\
+            Drag the name of a file or use the arrows beside it to move it up or down, or ✕ to close it.\n\
+            ⊞ and ⊟ beside the name of a file expand or collapse all of its scopes.\n\
+            Press V to flip between the file and RAM layouts.\n\
+            Arrow keys move the selection between siblings and into children or parents, Home/End jump to the first or last sibling and Enter zooms to it.\n\
+            Right-click on a scope to copy its details.\n\
+            Double-click to reset view.";
+                let help_response = ui
+                    .colored_label(ui.visuals().widgets.inactive.text_color(), "❓")
+                    .on_hover_text(help);
+                // Screen readers would otherwise only announce the icon.
+                help_response.widget_info(|| WidgetInfo::labeled(WidgetType::Label, help));
+                ui.menu_button("Legend", |ui| legend_ui(ui, options))
+                    .response
+                    .on_hover_text("What the colors of the scopes mean");
+                ui.menu_button("Display settings", |ui| display_settings_ui(ui, options))
+                    .response
+                    .on_hover_text("Size and spacing of the scopes");
+                ui.selectable_value(&mut options.theme, Theme::Dark, "🌙")
+                    .on_hover_text("Dark theme");
+                ui.selectable_value(&mut options.theme, Theme::Light, "☀")
+                    .on_hover_text("Light theme, easier to read in bright surroundings");
+
+                ui.separator();
+
+                if ui
+                    .button("Fit all")
+                    .on_hover_text("Zoom out to show the whole of every file, same as double-clicking")
+                    .clicked()
+                {
+                    options.zoom_to_relative_bytes_range =
+                        Some((ui.input(|i| i.time), (0, max_bytes(options, files))));
+                }
+                let selected = options
+                    .selected
+                    .as_ref()
+                    .and_then(|selected| selected.resolve(files));
+                if ui
+                    .add_enabled(selected.is_some(), Button::new("Zoom to selection"))
+                    .on_hover_text("Zoom to the scope last clicked on")
+                    .on_disabled_hover_text("Click on a scope to select it first")
+                    .clicked()
+                {
+                    if let Some(node) = selected {
+                        options.zoom_to_relative_bytes_range =
+                            Some((ui.input(|i| i.time), (node.bytes_start, node.bytes_end)));
+                    }
+                }
+
+                ui.separator();
+
+                let previous_view = options.layout_view;
+                // Don't switch views while the user is typing into a text field.
+                if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(Key::V)) {
+                    options.layout_view = options.layout_view.toggled();
+                }
+                ui.label("view:");
+                for (view, label) in [
+                    (LayoutView::Both, "both"),
+                    (LayoutView::File, "file"),
+                    (LayoutView::Ram, "RAM"),
+                ] {
+                    ui.selectable_value(&mut options.layout_view, view, label)
+                        .on_hover_text("Press V to flip between the file and RAM layouts");
+                }
+                // Addresses span a different range than file offsets, so start over zoomed out.
+                if options.layout_view != previous_view {
+                    options.canvas_width_bytes = 0.0;
+                    options.sideways_pan_in_points = 0.0;
+                }
+
+                ui.separator();
+
+                ui.selectable_value(
+                    &mut options.visualization,
+                    Visualization::Timeline,
+                    "timeline",
+                )
+                .on_hover_text("Lay out scopes along the bytes of the file");
+                ui.selectable_value(
+                    &mut options.visualization,
+                    Visualization::Treemap,
+                    "treemap",
+                )
+                .on_hover_text(
+                    "Draw scopes as tiles with areas proportional to their sizes, so even tiny sections can be clicked",
+                );
+
+                ui.separator();
+
+                ui.checkbox(&mut options.to_scale, "Draw to scale").on_hover_text(
+                    "When off, each scope is split evenly between its children.\n\
+                    This shows how scopes are nested, but positions and widths no longer match offsets and sizes.",
+                );
+                ui.checkbox(&mut options.label_sizes, "Show sizes");
+                ui.checkbox(&mut options.hex_offsets, "Hex")
+                    .on_hover_text("Write offsets and addresses in hexadecimal rather than decimal");
+                ui.checkbox(&mut options.binary_units, "KiB")
+                    .on_hover_text("Write sizes in units of 1024 bytes rather than 1000 bytes");
+                ui.checkbox(&mut options.tint_files, "Tint files")
+                    .on_hover_text("Give the scopes of each file a slightly different hue");
+                ui.label("color by:");
+                ui.selectable_value(&mut options.color_by, ColorBy::Size, "size");
+                ui.selectable_value(&mut options.color_by, ColorBy::Type, "type")
+                    .on_hover_text("Give code, data, debug info and so on a color each");
+                ui.selectable_value(&mut options.color_by, ColorBy::Entropy, "entropy")
+                    .on_hover_text(
+                        "Show how random the bytes look.\n\
+                        Compressed or encrypted data is close to 8 bits per byte.",
+                    );
+                let mut logarithmic = options.size_scale == SizeScale::Logarithmic;
+                if ui
+                    .add_enabled(
+                        options.color_by == ColorBy::Size,
+                        Checkbox::new(&mut logarithmic, "Log colors"),
+                    )
+                    .on_hover_text("Color scopes by the logarithm of their size")
+                    .changed()
+                {
+                    options.size_scale = if logarithmic {
+                        SizeScale::Logarithmic
+                    } else {
+                        SizeScale::Linear
+                    };
+                }
+
+                ui.separator();
+
+                ui.label("Open files zoomed to:")
+                    .on_hover_text("Name of a section to zoom to when a file is loaded");
+                ui.add(
+                    TextEdit::singleline(&mut options.initial_zoom_section)
+                        .hint_text(".text")
+                        .desired_width(80.0),
+                );
+
+                ui.separator();
+
+                search_ui(ui, options, files);
+
+                ui.separator();
+
+                ui.label("offsets from:");
+                ui.radio_value(
+                    &mut options.timeline_origin,
+                    TimelineOrigin::Absolute,
+                    "file start",
+                );
+                ui.radio_value(
+                    &mut options.timeline_origin,
+                    TimelineOrigin::Selection,
+                    "selection",
+                )
+                .on_hover_text("Count the timeline from the start of the selected scope");
+                ui.checkbox(&mut options.pin_timeline, "pinned")
+                    .on_hover_text("Keep the offsets in view while scrolling through many files");
+
+                ui.separator();
+
+                ui.label("click to:");
+                ui.radio_value(&mut options.click_action, ClickAction::Zoom, "zoom")
+                    .on_hover_text("Click to select and zoom, double-click to focus");
+                ui.radio_value(&mut options.click_action, ClickAction::Select, "select")
+                    .on_hover_text("Click to select and show details, double-click to zoom");
+
+                ui.separator();
+
+                ui.menu_button("Section types", |ui| {
+                    for category in SectionTypeCategory::ALL {
+                        let mut shown = options.type_filter.contains(&category);
+                        if ui.checkbox(&mut shown, format!("{category:?}")).changed() {
+                            if shown {
+                                options.type_filter.insert(category);
+                            } else {
+                                options.type_filter.remove(&category);
+                            }
+                        }
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut options.hide_filtered, "Hide unchecked")
+                        .on_hover_text("Hide scopes of unchecked types instead of dimming them");
+                })
+                .response
+                .on_hover_text("Scopes of unchecked types are dimmed");
+
+                ui.menu_button("Show", |ui| {
+                    ui.checkbox(&mut options.show_links, "Links").on_hover_text(
+                        "Draw arrows from the selected section to the sections it refers to, e.g. its string table",
+                    );
+                    ui.checkbox(&mut options.show_minimap, "Minimap").on_hover_text(
+                        "Show the whole width of the files above the scopes, drag the box or click to pan",
                     );
+                    ui.checkbox(&mut options.show_hex_dump, "Hex dump")
+                        .on_hover_text("Show the bytes of the selected scope beside the scopes");
+                    ui.checkbox(&mut options.show_strings, "Strings")
+                        .on_hover_text("List the printable strings in the selected scope");
+                    ui.checkbox(&mut options.show_histogram, "Histogram").on_hover_text(
+                        "Show how many sections there are of each size and the largest ones",
+                    );
+                })
+                .response
+                .on_hover_text("Links between sections and panels beside the scopes");
+
+                ui.separator();
+
+                baseline_ui(ui, options, files);
 
                 ui.separator();
 
-                ui.checkbox(&mut options.to_scale, "Draw to scale");
+                ui.menu_button("Copy", |ui| {
+                    if ui
+                        .button("As Markdown")
+                        .on_hover_text("Copy a table of the sizes of the sections of each file")
+                        .clicked()
+                    {
+                        let tables: Vec<String> = files
+                            .iter()
+                            .map(|file| crate::report::markdown(file, options.baseline.as_ref()))
+                            .collect();
+                        ui.output_mut(|o| o.copied_text = tables.join("\n"));
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Debug dump")
+                        .on_hover_text(
+                            "Copy the options and the loaded trees, without the file contents, for attaching to a bug report",
+                        )
+                        .clicked()
+                    {
+                        ui.output_mut(|o| o.copied_text = debug_dump(options, files));
+                        ui.close_menu();
+                    }
+                });
 
                 options.sorting.ui(ui);
             });
@@ -155,76 +1083,1016 @@ pub fn ui(ui: &mut egui::Ui, options: &mut Options, files: &mut [ExecutableFile]
 
     ui.separator();
 
-    Frame::dark_canvas(ui.style()).show(ui, |ui| {
+    keyboard_navigation(ui, options, files);
+
+    if options.focused.is_some() {
+        focus_breadcrumb_ui(ui, options, files);
+        ui.separator();
+    }
+
+    if options.click_action == ClickAction::Select {
+        selection_details_ui(ui, options, files);
+    }
+
+    if options.show_hex_dump {
+        SidePanel::right("hex_dump")
+            .resizable(false)
+            .show_inside(ui, |ui| hex_dump_ui(ui, options, files));
+    }
+
+    if options.show_strings {
+        SidePanel::right("strings").show_inside(ui, |ui| strings_ui(ui, options, files));
+    }
+
+    if options.show_histogram {
+        SidePanel::right("histogram").show_inside(ui, |ui| histogram::ui(ui, options, files));
+    }
+
+    if options.visualization == Visualization::Treemap {
+        let response = Frame::canvas(ui.style())
+            .show(ui, |ui| treemap::ui(ui, options, files))
+            .response;
+        options.canvas_rect = Some(response.rect);
+        return;
+    }
+
+    let response = Frame::canvas(ui.style()).show(ui, |ui| {
+        // Allocated above the scopes, but painted once the extent and width of the canvas are known.
+        let minimap = options.show_minimap.then(|| {
+            ui.allocate_response(
+                vec2(ui.available_width(), MINIMAP_HEIGHT),
+                Sense::click_and_drag(),
+            )
+        });
         let available_height = ui.max_rect().bottom() - ui.min_rect().bottom();
-        ScrollArea::vertical().show(ui, |ui| {
-            let mut canvas = ui.available_rect_before_wrap();
-            canvas.max.y = f32::INFINITY;
-            let response = ui.interact(canvas, ui.id(), Sense::click_and_drag());
+        let (canvas_width, max_bytes) = ScrollArea::vertical()
+            .show(ui, |ui| {
+                let mut canvas = ui.available_rect_before_wrap();
+                canvas.max.y = f32::INFINITY;
+                let response = ui.interact(canvas, ui.id(), Sense::click_and_drag());
+
+                let min_bytes = 0;
+                let max_bytes = max_bytes(options, files);
+
+                let info = Info {
+                    ctx: ui.ctx().clone(),
+                    canvas,
+                    response,
+                    painter: ui.painter_at(canvas),
+                    text_height: 15.0, // TODO
+                    start_bytes: min_bytes,
+                    stop_bytes: max_bytes,
+                    font_id: TextStyle::Body.resolve(ui.style()),
+                    text_color: ui.visuals().strong_text_color(),
+                    canvas_color: ui.visuals().extreme_bg_color,
+                    painted_rect: Cell::new(Rect::NOTHING),
+                    size_range: options.size_range(files),
+                    accessible: RefCell::new(vec![]),
+                    links: SelectedLinks::new(options, files),
+                    search: options.search.to_lowercase(),
+                };
+
+                if options.canvas_width_bytes <= 0.0 {
+                    options.canvas_width_bytes = (max_bytes - min_bytes) as f32;
+                }
 
-            let min_bytes = 0;
-            let max_bytes = files
-                .iter()
-                .map(|file| file.file_root.bytes_end)
-                .max()
-                .unwrap_or(100);
-
-            let info = Info {
-                ctx: ui.ctx().clone(),
-                canvas,
-                response,
-                painter: ui.painter_at(canvas),
-                text_height: 15.0, // TODO
-                start_bytes: min_bytes,
-                stop_bytes: max_bytes,
-                font_id: TextStyle::Body.resolve(ui.style()),
-            };
+                interact_with_canvas(options, &info.response, &info);
 
-            interact_with_canvas(options, &info.response, &info);
+                let where_to_put_timeline = info.painter.add(Shape::Noop);
 
-            let where_to_put_timeline = info.painter.add(Shape::Noop);
+                let max_y = ui_canvas(options, &info, files);
+                drag_file_ui(options, &info, files);
+                info.response
+                    .context_menu(|ui| scope_context_menu(ui, options, files));
 
-            let max_y = ui_canvas(options, &info, (min_bytes, max_bytes), files);
+                if let Some(links) = &info.links {
+                    links.paint(&info);
+                }
 
-            let mut used_rect = canvas;
-            used_rect.max.y = max_y;
+                // These widgets are not interactive, so they don't block clicks and drags on the canvas.
+                for item in info.accessible.take() {
+                    ui.interact(item.rect, item.id, Sense::focusable_noninteractive())
+                        .widget_info(|| item.info.clone());
+                }
 
-            // Fill out space that we don't use so that the `ScrollArea` doesn't collapse in height:
-            used_rect.max.y = used_rect.max.y.max(used_rect.min.y + available_height);
+                // Make sure the scroll area covers everything that was painted, including room for the timeline labels at the bottom.
+                // Horizontally everything is clipped to the canvas, panning is used to view the rest.
+                let painted_rect = info.painted_rect.get();
+                let mut used_rect = canvas;
+                used_rect.max.y = max_y;
+                if painted_rect.is_positive() {
+                    used_rect.max.y = used_rect.max.y.max(painted_rect.max.y + info.text_height);
+                }
 
-            let timeline = paint_timeline(&info, used_rect, options, min_bytes);
-            info.painter
-                .set(where_to_put_timeline, Shape::Vec(timeline));
+                // Fill out space that we don't use so that the `ScrollArea` doesn't collapse in height:
+                used_rect.max.y = used_rect.max.y.max(used_rect.min.y + available_height);
+
+                let origin = match options.timeline_origin {
+                    TimelineOrigin::Absolute => None,
+                    TimelineOrigin::Selection => options
+                        .selected
+                        .as_ref()
+                        .and_then(|selected| selected.resolve(files))
+                        .map(|section| section.bytes_start),
+                };
+                let origin = origin.unwrap_or(min_bytes);
+                // Label the timeline with addresses when only memory is drawn.
+                let addresses = match &options.focused {
+                    Some(focused) => focused.layout == Layout::Ram,
+                    None => options.layout_view == LayoutView::Ram,
+                };
+                if options.pin_timeline {
+                    // The labels are painted on top of the scopes, at the edges of the visible part of the scroll area.
+                    let visible_rect = ui.clip_rect().intersect(used_rect);
+                    let (lines, labels) =
+                        paint_timeline(&info, used_rect, visible_rect, options, origin, addresses);
+                    info.painter.set(where_to_put_timeline, Shape::Vec(lines));
+                    for y in [
+                        visible_rect.min.y..=visible_rect.min.y + info.text_height,
+                        visible_rect.max.y - info.text_height..=visible_rect.max.y,
+                    ] {
+                        let strip = Rect::from_x_y_ranges(visible_rect.x_range(), y);
+                        info.painter.rect_filled(
+                            strip,
+                            0.0,
+                            info.canvas_color.gamma_multiply(PINNED_TIMELINE_OPACITY),
+                        );
+                    }
+                    info.painter.extend(labels);
+                } else {
+                    let (mut lines, labels) =
+                        paint_timeline(&info, used_rect, used_rect, options, origin, addresses);
+                    lines.extend(labels);
+                    info.painter.set(where_to_put_timeline, Shape::Vec(lines));
+                }
 
-            ui.allocate_rect(used_rect, Sense::hover());
-        });
+                if !options.to_scale {
+                    paint_not_to_scale_watermark(&info, ui.clip_rect().intersect(used_rect));
+                }
+
+                ui.allocate_rect(used_rect, Sense::hover());
+                (canvas.width(), max_bytes)
+            })
+            .inner;
+        if let Some(minimap) = minimap {
+            minimap_ui(ui, &minimap, options, files, canvas_width, max_bytes);
+        }
     });
+    options.canvas_rect = Some(response.response.rect);
 }
 
-fn ui_canvas(
+/// Paints the top level scopes of every file across the whole width of `response.rect`, and a box around the bytes in view.
+/// Dragging the box pans the view, clicking or dragging anywhere else centers the view there.
+///
+/// `canvas_width` is the width of the canvas the scopes are drawn on and `max_bytes` the end of its byte axis.
+fn minimap_ui(
+    ui: &Ui,
+    response: &Response,
     options: &mut Options,
-    info: &Info,
-    (min_bytes, max_bytes): (BytesCount, BytesCount),
-    files: &mut [ExecutableFile],
-) -> f32 {
-    if options.canvas_width_bytes <= 0.0 {
-        options.canvas_width_bytes = (max_bytes - min_bytes) as f32;
-        options.zoom_to_relative_bytes_range = None;
+    files: &[ExecutableFile],
+    canvas_width: f32,
+    max_bytes: BytesCount,
+) {
+    let rect = response.rect;
+    if max_bytes == 0 || options.canvas_width_bytes <= 0.0 || !rect.is_positive() {
+        return;
     }
+    let painter = ui.painter_at(rect);
+    let x_from_bytes = |bytes: f64| rect.min.x + (bytes / max_bytes as f64) as f32 * rect.width();
+    let bytes_from_x = |x: f32| (x - rect.min.x) as f64 / rect.width() as f64 * max_bytes as f64;
+
+    let roots: Vec<&FileNode> = files
+        .iter()
+        .flat_map(|file| {
+            [Layout::File, Layout::Ram]
+                .into_iter()
+                .filter(|layout| options.layout_view.shows(*layout))
+                .map(|layout| file.root(layout))
+        })
+        .collect();
+    let row_height = rect.height() / roots.len().max(1) as f32;
+    for (row, root) in roots.into_iter().enumerate() {
+        let top = rect.min.y + row as f32 * row_height;
+        for child in &root.children {
+            let x_range =
+                x_from_bytes(child.bytes_start as f64)..=x_from_bytes(child.bytes_end as f64);
+            // Colored by type, so the kind of each region can be recognized whatever the scopes are colored by.
+            let color = if matches!(child.ty, SectionType::Gap) {
+                GAP_COLOR
+            } else {
+                color_from_type(&child.ty, &child.name)
+            };
+            painter.rect_filled(
+                Rect::from_x_y_ranges(x_range, top..=top + row_height - 1.0),
+                0.0,
+                color,
+            );
+        }
+    }
+
+    // The same mapping as `Info::bytes_from_point`, at the edges of the canvas.
+    let visible_start = -options.sideways_pan_in_points as f64 * options.canvas_width_bytes as f64
+        / canvas_width as f64;
+    let visible_bytes = options.canvas_width_bytes as f64;
+    let viewport = Rect::from_x_y_ranges(
+        x_from_bytes(visible_start)..=x_from_bytes(visible_start + visible_bytes),
+        rect.y_range(),
+    );
+    painter.rect_filled(viewport, 0.0, MINIMAP_VIEWPORT_FILL);
+    painter.rect_stroke(
+        viewport,
+        0.0,
+        Stroke::new(1.5, ui.visuals().strong_text_color()),
+    );
+
+    // Remember whether the drag started on the box, the box moves along with the pointer after that.
+    let dragging_viewport_id = response.id.with("dragging_viewport");
+    if response.drag_started() {
+        let on_viewport = response
+            .interact_pointer_pos()
+            .is_some_and(|pos| viewport.expand2(vec2(2.0, 0.0)).contains(pos));
+        ui.data_mut(|d| d.insert_temp(dragging_viewport_id, on_viewport));
+    }
+    let dragging_viewport = response.dragged()
+        && ui
+            .data(|d| d.get_temp(dragging_viewport_id))
+            .unwrap_or(false);
+
+    let new_start = if dragging_viewport {
+        Some(visible_start + bytes_from_x(rect.min.x + response.drag_delta().x))
+    } else if response.clicked() || response.dragged() {
+        response
+            .interact_pointer_pos()
+            .map(|pos| bytes_from_x(pos.x) - visible_bytes / 2.0)
+    } else {
+        None
+    };
+    if let Some(new_start) = new_start {
+        options.sideways_pan_in_points = (-new_start * canvas_width as f64 / visible_bytes) as f32;
+        options.zoom_to_relative_bytes_range = None;
+        ui.ctx().request_repaint();
+    }
+    response
+        .clone()
+        .on_hover_text("Drag the box to pan, or click to center the view there");
+}
+
+/// Reminds the user that the timeline doesn't line up with the scopes when [`Options::to_scale`] is off.
+fn paint_not_to_scale_watermark(info: &Info, visible_rect: Rect) {
+    info.painter.text(
+        visible_rect.right_bottom() - vec2(8.0, 8.0 + info.text_height),
+        Align2::RIGHT_BOTTOM,
+        "not to scale: positions show nesting, not offsets",
+        FontId::proportional(20.0),
+        info.text_color.gamma_multiply(0.25),
+    );
+}
+
+/// A window with the details of [`Options::selected`] that stays open until closed or the selection is cleared.
+fn selection_details_ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    let Some(selected) = options.selected.clone() else {
+        return;
+    };
+    let (Some(file), Some(section)) = (files.get(selected.file), selected.resolve(files)) else {
+        options.selected = None;
+        return;
+    };
+    let root = file.root(selected.layout);
+    let entropy = (options.color_by == ColorBy::Entropy).then(|| file.entropy());
+    let scope_file = ScopeFile {
+        index: selected.file,
+        layout: selected.layout,
+        data: &file.data,
+        len: root.len(),
+        is_baseline: options
+            .baseline
+            .as_ref()
+            .is_some_and(|baseline| baseline.is_file(file)),
+        entry_point: file.entry_point,
+        entropy: entropy.as_deref(),
+    };
+    let parent_len = selected
+        .children
+        .split_last()
+        .and_then(|(_, parent)| root.descendant(parent))
+        .map(FileNode::len);
+    let diff = options.compare_to_baseline(&scope_file, section);
+
+    let mut open = true;
+    Window::new("Selection")
+        .open(&mut open)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            paint_section_details(
+                ui,
+                Numbers::of(options),
+                &scope_file,
+                parent_len,
+                diff,
+                section,
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Zoom to").clicked() {
+                    options.zoom_to_relative_bytes_range = Some((
+                        ui.input(|i| i.time),
+                        (section.bytes_start, section.bytes_end),
+                    ));
+                }
+                if ui.button("Focus").clicked() {
+                    focus(ui, options, selected.clone(), section);
+                }
+            });
+        });
+    if !open {
+        options.selected = None;
+    }
+}
+
+/// The bytes in the file of [`Options::selected`], as offset, hex and ASCII columns.
+fn hex_dump_ui(ui: &mut Ui, options: &Options, files: &[ExecutableFile]) {
+    const BYTES_PER_ROW: usize = 16;
+
+    ui.heading("Hex dump");
+    let Some(selected) = &options.selected else {
+        ui.label("Click on a scope to show its bytes.");
+        return;
+    };
+    let (Some(file), Some(section)) = (files.get(selected.file), selected.resolve(files)) else {
+        return;
+    };
+    ui.monospace(&section.name);
+    if section.category() == SectionTypeCategory::Bss {
+        ui.label("Zero initialized, this takes up no space in the file.");
+        return;
+    }
+    let start = section.file_bytes_start as usize;
+    let Some(bytes) = section.file_data(&file.data) else {
+        ui.label("This lies outside of the file.");
+        return;
+    };
+    if bytes.is_empty() {
+        ui.label("Empty.");
+        return;
+    }
+
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    let rows = bytes.len().div_ceil(BYTES_PER_ROW);
+    // Only the visible rows are laid out, so even huge sections stay fast.
+    ScrollArea::vertical()
+        .id_source(("hex_dump", selected.file, start))
+        .show_rows(ui, row_height, rows, |ui, visible_rows| {
+            for row in visible_rows {
+                let row_start = row * BYTES_PER_ROW;
+                let chunk = &bytes[row_start..(row_start + BYTES_PER_ROW).min(bytes.len())];
+                let mut line = format!("{:08x} ", start + row_start);
+                for i in 0..BYTES_PER_ROW {
+                    match chunk.get(i) {
+                        Some(byte) => line.push_str(&format!(" {byte:02x}")),
+                        None => line.push_str("   "),
+                    }
+                }
+                line.push_str("  ");
+                line.extend(chunk.iter().map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                }));
+                ui.monospace(line);
+            }
+        });
+}
+
+/// The printable strings in the file bytes of [`Options::selected`], clicking one zooms to it.
+fn strings_ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    ui.heading("Strings");
+    ui.horizontal(|ui| {
+        ui.label("min length:");
+        ui.add(DragValue::new(&mut options.min_string_len).clamp_range(1..=64));
+    });
+    let Some(selected) = options.selected.clone() else {
+        ui.label("Click on a scope to list its strings.");
+        return;
+    };
+    let (Some(file), Some(section)) = (files.get(selected.file), selected.resolve(files)) else {
+        return;
+    };
+    ui.monospace(&section.name);
+    let Some(bytes) = section.file_data(&file.data) else {
+        ui.label("This lies outside of the file.");
+        return;
+    };
+
+    // Scanning a large section takes a while, so the strings are kept until another scope or length is picked.
+    let cache_id = Id::new("strings");
+    let key = Id::new((
+        file.data.as_ptr(),
+        section.file_bytes_start,
+        section.file_bytes_end,
+        options.min_string_len,
+    ));
+    let cached = ui
+        .data(|d| d.get_temp::<(Id, Arc<Vec<(usize, String)>>)>(cache_id))
+        .filter(|(cached_key, _)| *cached_key == key);
+    let strings = match cached {
+        Some((_, strings)) => strings,
+        None => {
+            let strings = Arc::new(printable_strings(bytes, options.min_string_len));
+            ui.data_mut(|d| d.insert_temp(cache_id, (key, strings.clone())));
+            strings
+        }
+    };
+    ui.label(format!("{} strings", strings.len()));
+
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    ScrollArea::vertical()
+        .id_source(("strings", selected.file, section.file_bytes_start))
+        .show_rows(ui, row_height, strings.len(), |ui, visible_rows| {
+            for (offset, string) in &strings[visible_rows] {
+                let line = format!(
+                    "{:08x}  {string}",
+                    section.file_bytes_start as usize + offset
+                );
+                let response =
+                    ui.add(Label::new(RichText::new(line).monospace()).sense(Sense::click()));
+                if response.on_hover_text("Zoom to this string").clicked() {
+                    // Relative to the scope, so this also works for scopes in the RAM layout.
+                    let start = section.bytes_start + *offset as u64;
+                    options.zoom_to_relative_bytes_range =
+                        Some((ui.input(|i| i.time), (start, start + string.len() as u64)));
+                }
+            }
+        });
+}
+
+/// Each run of at least `min_len` printable characters in `bytes` and its offset, like the `strings` tool.
+/// Besides ASCII, characters encoded as UTF-8 are included so text in other languages is found too.
+fn printable_strings(bytes: &[u8], min_len: usize) -> Vec<(usize, String)> {
+    let mut strings = vec![];
+    let mut run_start = 0;
+    let mut run_chars = 0;
+    let mut i = 0;
+    while i <= bytes.len() {
+        let char_len = bytes.get(i).and_then(|&byte| match byte {
+            b' ' | b'\t' => Some(1),
+            _ if byte.is_ascii_graphic() => Some(1),
+            0xc2..=0xf4 => {
+                let len = byte.leading_ones() as usize;
+                let encoded = bytes.get(i..i + len)?;
+                let c = std::str::from_utf8(encoded).ok()?.chars().next()?;
+                (!c.is_control()).then_some(len)
+            }
+            _ => None,
+        });
+        match char_len {
+            Some(len) => {
+                run_chars += 1;
+                i += len;
+            }
+            None => {
+                if run_chars >= min_len {
+                    // Only whole printable characters were added to the run, so it is valid UTF-8.
+                    let string = String::from_utf8_lossy(&bytes[run_start..i]).into_owned();
+                    strings.push((run_start, string));
+                }
+                run_chars = 0;
+                i += 1;
+                run_start = i;
+            }
+        }
+    }
+    strings
+}
+
+/// The path to [`Options::focused`], clicking an ancestor focuses it instead.
+fn focus_breadcrumb_ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    let Some(focused) = options.focused.clone() else {
+        return;
+    };
+    let Some(file) = files.get(focused.file) else {
+        options.focused = None;
+        return;
+    };
+    if ui.input(|i| i.key_pressed(Key::Escape)) {
+        leave_focus(ui, options);
+        return;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        if ui.button("Full view").clicked() {
+            leave_focus(ui, options);
+            return;
+        }
+        ui.label(&file.name);
+        let mut node = file.root(focused.layout);
+        for depth in 0..=focused.children.len() {
+            ui.label("›");
+            if depth == focused.children.len() {
+                ui.strong(&node.name);
+                break;
+            }
+            if ui.link(&node.name).clicked() {
+                focus(
+                    ui,
+                    options,
+                    NodePath {
+                        children: focused.children[..depth].to_vec(),
+                        ..focused.clone()
+                    },
+                    node,
+                );
+            }
+            node = &node.children[focused.children[depth]];
+        }
+    });
+}
+
+/// Focus on `node` at `path` and zoom to fit it.
+fn focus(ui: &Ui, options: &mut Options, path: NodePath, node: &FileNode) {
+    options.focused = Some(path);
+    options.zoom_to_relative_bytes_range =
+        Some((ui.input(|i| i.time), (node.bytes_start, node.bytes_end)));
+}
+
+/// Go back to drawing every file, zoomed out to fit them.
+fn leave_focus(ui: &Ui, options: &mut Options) {
+    options.focused = None;
+    options.zoom_to_relative_bytes_range = None;
+    options.canvas_width_bytes = 0.0;
+    options.sideways_pan_in_points = 0.0;
+    ui.ctx().request_repaint();
+}
+
+/// Moves [`Options::selected`] with the arrow keys, Home and End, and zooms to it with Enter.
+/// Siblings are visited in the order of their offsets, whatever order they are drawn in.
+fn keyboard_navigation(ui: &Ui, options: &mut Options, files: &mut [ExecutableFile]) {
+    // Don't steal keys from the search field.
+    if ui.ctx().wants_keyboard_input() {
+        return;
+    }
+    let keys = [
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::Home,
+        Key::End,
+        Key::Enter,
+    ];
+    let Some(key) = keys
+        .into_iter()
+        .find(|key| ui.input_mut(|i| i.consume_key(Modifiers::NONE, *key)))
+    else {
+        return;
+    };
+
+    let selected = options
+        .selected
+        .clone()
+        .filter(|selected| selected.resolve(files).is_some());
+    let Some(selected) = selected else {
+        // Start from the top of what is drawn.
+        options.selected = options.focused.clone().or_else(|| {
+            let layout = match options.layout_view {
+                LayoutView::Ram => Layout::Ram,
+                LayoutView::Both | LayoutView::File => Layout::File,
+            };
+            (!files.is_empty()).then_some(NodePath {
+                file: 0,
+                layout,
+                children: vec![],
+            })
+        });
+        return;
+    };
+
+    let file = &mut files[selected.file];
+    let root = match selected.layout {
+        Layout::File => &mut file.file_root,
+        Layout::Ram => &mut file.ram_root,
+    };
+    let mut path = selected.children.clone();
+    match key {
+        Key::Enter => {
+            if let Some(node) = root.descendant(&path) {
+                options.zoom_to_relative_bytes_range =
+                    Some((ui.input(|i| i.time), (node.bytes_start, node.bytes_end)));
+            }
+            return;
+        }
+        Key::ArrowUp => {
+            // Stay within the focused scope.
+            let at_focus_root = options
+                .focused
+                .as_ref()
+                .is_some_and(|focused| focused.children == path);
+            if !at_focus_root {
+                path.pop();
+            }
+        }
+        Key::ArrowDown => {
+            if let Some(node) = root.descendant_mut(&path) {
+                if let Some(&first) = children_by_offset(node).first() {
+                    node.inspector_collapsed = false;
+                    path.push(first);
+                }
+            }
+        }
+        _ => {
+            let (Some(&index), Some(parent)) = (
+                path.last(),
+                root.descendant(&path[..path.len().saturating_sub(1)]),
+            ) else {
+                return;
+            };
+            let siblings = children_by_offset(parent);
+            let position = siblings.iter().position(|i| *i == index).unwrap_or(0);
+            let new_position = match key {
+                Key::ArrowLeft => position.checked_sub(1),
+                Key::ArrowRight => Some(position + 1),
+                Key::Home => Some(0),
+                _ => siblings.len().checked_sub(1),
+            };
+            if let Some(&new_index) = new_position.and_then(|p| siblings.get(p)) {
+                *path.last_mut().unwrap() = new_index;
+            }
+        }
+    }
+    options.selected = Some(NodePath {
+        children: path,
+        ..selected
+    });
+}
+
+/// Indexes of the children of `node`, ordered by where they start.
+fn children_by_offset(node: &FileNode) -> Vec<usize> {
+    let mut indexes: Vec<usize> = (0..node.children.len()).collect();
+    indexes.sort_by_key(|i| node.children[*i].bytes_start);
+    indexes
+}
+
+fn debug_dump(options: &Options, files: &[ExecutableFile]) -> String {
+    let mut dump = format!("{options:#?}\n");
+    for file in files {
+        dump.push_str(&format!("{file:#?}\n"));
+    }
+    dump
+}
+
+fn baseline_ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    let text = match &options.baseline {
+        Some(baseline) => format!("Baseline: {}", baseline.name()),
+        None => "Baseline".to_owned(),
+    };
+    ui.menu_button(text, |ui| {
+        if ui
+            .selectable_label(options.baseline.is_none(), "None")
+            .clicked()
+        {
+            options.baseline = None;
+            ui.close_menu();
+        }
+        for file in files {
+            let pinned = options
+                .baseline
+                .as_ref()
+                .is_some_and(|baseline| baseline.is_file(file));
+            if ui.selectable_label(pinned, &file.name).clicked() {
+                options.baseline = Some(Baseline::new(file));
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Pin a file to compare the other files against");
+    ui.add_enabled(
+        options.baseline.is_some(),
+        Checkbox::new(&mut options.color_by_baseline, "vs baseline"),
+    )
+    .on_hover_text(
+        "Color scopes red if they grew, green if they shrank, yellow if only their contents changed and blue if they are new",
+    );
+    if let Some(baseline) = &options.baseline {
+        let numbers = Numbers::of(options);
+        ui.menu_button("Summary", |ui| {
+            baseline_summary_ui(ui, numbers, baseline, files);
+        })
+        .response
+        .on_hover_text(
+            "How much each file grew compared to the baseline and which sections grew the most",
+        );
+    }
+}
+
+/// The total change of each file compared to `baseline`, its top growing sections and the sections it no longer has.
+fn baseline_summary_ui(
+    ui: &mut Ui,
+    numbers: Numbers,
+    baseline: &Baseline,
+    files: &[ExecutableFile],
+) {
+    const GROWERS: usize = 5;
+
+    let mut compared = false;
+    for file in files.iter().filter(|file| !baseline.is_file(file)) {
+        if compared {
+            ui.separator();
+        }
+        compared = true;
+        let summary = baseline.summary(file, GROWERS);
+        ui.strong(format!("{} vs {}", file.name, baseline.name()));
+        ui.label(format!("total: {}", numbers.delta(summary.total_delta)));
+        Grid::new(("baseline_summary", &file.name))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (name, diff) in &summary.growers {
+                    let (color, text) = match diff {
+                        NodeDiff::Changed { delta } => {
+                            (color_from_diff(*diff), numbers.delta(*delta))
+                        }
+                        _ => (color_from_diff(*diff), "added".to_owned()),
+                    };
+                    ui.colored_label(color, name);
+                    ui.monospace(text);
+                    ui.end_row();
+                }
+                for (name, len) in summary.removed.iter().take(GROWERS) {
+                    ui.colored_label(REMOVED_COLOR, name);
+                    ui.monospace(format!("removed, {}", numbers.delta(-(*len as i64))));
+                    ui.end_row();
+                }
+            });
+        if summary.removed.len() > GROWERS {
+            ui.label(format!(
+                "and {} more removed sections",
+                summary.removed.len() - GROWERS
+            ));
+        }
+        if summary.growers.is_empty() && summary.removed.is_empty() {
+            ui.label("No section grew or was removed.");
+        }
+    }
+    if !compared {
+        ui.label("Load another file to compare against the baseline.");
+    }
+}
+
+/// The text field for [`Options::search`].
+/// Enter zooms to the first match and Escape clears the search.
+fn search_ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    ui.label("search:");
+    let response = ui
+        .add(
+            TextEdit::singleline(&mut options.search)
+                .hint_text(".debug_str")
+                .desired_width(80.0),
+        )
+        .on_hover_text(
+            "Dim scopes whose name doesn't contain this. Press Enter to zoom to the first match",
+        );
+    if !response.lost_focus() {
+        return;
+    }
+    // Consumed so that Escape doesn't also leave focus.
+    if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+        options.search.clear();
+    } else if !options.search.is_empty()
+        && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Enter))
+    {
+        let query = options.search.to_lowercase();
+        let first_match = files
+            .iter()
+            .flat_map(|file| {
+                [Layout::File, Layout::Ram]
+                    .into_iter()
+                    .filter(|layout| options.layout_view.shows(*layout))
+                    .map(|layout| file.root(layout))
+            })
+            .flat_map(|root| root.iter().skip(1))
+            .find(|node| node.name.to_lowercase().contains(&query));
+        if let Some(node) = first_match {
+            options.zoom_to_relative_bytes_range =
+                Some((ui.input(|i| i.time), (node.bytes_start, node.bytes_end)));
+        }
+    }
+}
+
+/// Sliders for the sizes the scopes are drawn with, e.g. [`Options::rect_height`].
+fn display_settings_ui(ui: &mut Ui, options: &mut Options) {
+    Grid::new("display_settings")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("height");
+            ui.add(Slider::new(&mut options.rect_height, 8.0..=40.0).suffix(" pt"))
+                .on_hover_text("Height of each scope");
+            ui.end_row();
+
+            ui.label("spacing");
+            ui.add(Slider::new(&mut options.spacing, 0.0..=16.0).suffix(" pt"))
+                .on_hover_text("Space between a scope and the row of its children");
+            ui.end_row();
+
+            ui.label("rounding");
+            let max_rounding = 0.5 * options.rect_height;
+            ui.add(Slider::new(&mut options.rounding, 0.0..=max_rounding).suffix(" pt"))
+                .on_hover_text("Radius of the corners of each scope");
+            ui.end_row();
+
+            ui.label("cull width");
+            ui.add(Slider::new(&mut options.cull_width, 0.0..=4.0).suffix(" pt"))
+                .on_hover_text(
+                    "Scopes narrower than this aren't drawn at all, which is faster for files with many tiny sections",
+                );
+            ui.end_row();
+
+            ui.label("min width");
+            ui.add_enabled(
+                options.cull_width == 0.0,
+                Slider::new(&mut options.min_width, 0.0..=4.0).suffix(" pt"),
+            )
+            .on_hover_text("Draw narrower scopes this wide, so they can still be seen and hovered")
+            .on_disabled_hover_text("Only used when the cull width is 0");
+            ui.end_row();
+
+            let mut limit_depth = options.max_depth.is_some();
+            ui.checkbox(&mut limit_depth, "max depth")
+                .on_hover_text("Hide scopes nested deeper than this, for very deep trees");
+            let mut max_depth = options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+            ui.add_enabled(
+                limit_depth,
+                Slider::new(&mut max_depth, 1..=MAX_DEPTH_SLIDER).suffix(" rows"),
+            );
+            options.max_depth = limit_depth.then_some(max_depth);
+            ui.end_row();
+        });
+
+    if ui.button("Reset").clicked() {
+        let default = Options::default();
+        options.rect_height = default.rect_height;
+        options.spacing = default.spacing;
+        options.rounding = default.rounding;
+        options.cull_width = default.cull_width;
+        options.min_width = default.min_width;
+        options.max_depth = default.max_depth;
+    }
+}
+
+/// Explains the colors used by the current [`Options::color_by`], or the baseline colors when comparing.
+fn legend_ui(ui: &mut Ui, options: &Options) {
+    fn swatch(ui: &mut Ui, color: Rgba, label: &str) {
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(vec2(16.0, 16.0), Sense::hover());
+            ui.painter().rect_filled(rect, 4.0, color);
+            ui.label(label);
+        });
+    }
+
+    if options.color_by_baseline && options.baseline.is_some() {
+        swatch(
+            ui,
+            color_from_diff(NodeDiff::Changed { delta: 33_000 }),
+            "grew",
+        );
+        swatch(
+            ui,
+            color_from_diff(NodeDiff::Changed { delta: -33_000 }),
+            "shrank",
+        );
+        swatch(
+            ui,
+            color_from_diff(NodeDiff::Changed { delta: 0 }),
+            "same size, different contents",
+        );
+        swatch(ui, color_from_diff(NodeDiff::Identical), "identical");
+        swatch(ui, color_from_diff(NodeDiff::Added), "not in the baseline");
+        ui.label("The more a scope grew or shrank, the brighter it is.");
+        return;
+    }
+    match options.color_by {
+        ColorBy::Size => {
+            let (rect, _) = ui.allocate_exact_size(vec2(160.0, 16.0), Sense::hover());
+            let steps = 32;
+            let step_width = rect.width() / steps as f32;
+            for i in 0..steps {
+                let left = rect.left() + i as f32 * step_width;
+                let step_rect = Rect::from_x_y_ranges(left..=left + step_width, rect.y_range());
+                let fraction = (i as f32 + 0.5) / steps as f32;
+                ui.painter()
+                    .rect_filled(step_rect, 0.0, color_from_size(fraction));
+            }
+            ui.horizontal(|ui| {
+                ui.label("smallest");
+                ui.add_space(60.0);
+                ui.label("largest");
+            });
+            if options.size_scale == SizeScale::Logarithmic {
+                ui.label("Sizes are on a logarithmic scale.");
+            }
+        }
+        ColorBy::Type => {
+            for category in SectionTypeCategory::ALL {
+                swatch(ui, color_from_category(category), &format!("{category:?}"));
+            }
+        }
+        ColorBy::Entropy => {
+            let (rect, _) = ui.allocate_exact_size(vec2(160.0, 16.0), Sense::hover());
+            let steps = 32;
+            let step_width = rect.width() / steps as f32;
+            for i in 0..steps {
+                let left = rect.left() + i as f32 * step_width;
+                let step_rect = Rect::from_x_y_ranges(left..=left + step_width, rect.y_range());
+                let bits = (i as f32 + 0.5) / steps as f32 * MAX_BITS_PER_BYTE;
+                ui.painter()
+                    .rect_filled(step_rect, 0.0, color_from_entropy(bits));
+            }
+            ui.horizontal(|ui| {
+                ui.label("repetitive");
+                ui.add_space(60.0);
+                ui.label("random");
+            });
+            ui.label(format!(
+                "Entropy is measured over all the bytes of a scope,\n\
+                the strip within wide scopes over every {WINDOW} bytes of the file.\n\
+                RAM scopes use the bytes they are loaded from."
+            ));
+        }
+    }
+    swatch(ui, GAP_COLOR, "gap, not described by any section");
+    if options.tint_files {
+        ui.label("Files after the first are tinted a hue of their own.");
+    }
+}
 
+fn ui_canvas(options: &mut Options, info: &Info, files: &mut Vec<ExecutableFile>) -> f32 {
     // We paint the binaries top-down
+    let mut file_headers = vec![];
+    let mut closed = None;
+    let mut moved = None;
+    let file_count = files.len();
     let mut cursor_y = info.canvas.top();
     cursor_y += info.text_height; // Leave room for time labels
+    options
+        .name_groups
+        .keep_if_unchanged(options.hide_filtered, &options.type_filter, files);
+
+    for (index, file) in files.iter_mut().enumerate() {
+        if options
+            .focused
+            .as_ref()
+            .is_some_and(|focused| focused.file != index)
+        {
+            continue;
+        }
 
-    for file in files {
         // Visual separator between binaries:
         cursor_y += 2.0;
         let line_y = cursor_y;
         cursor_y += 2.0;
 
+        // Filled in once we know how tall the band of this file is, but painted behind it.
+        let where_to_put_band = info.painter.add(Shape::Noop);
+
         let text_pos = pos2(info.canvas.min.x, cursor_y);
 
-        paint_binary_info(info, file, text_pos);
+        let header = paint_binary_info(info, Numbers::of(options), index, file, text_pos);
+        file_headers.push((index, header));
+        // Buttons beside the header, the arrows are an alternative to dragging it.
+        let mut button_pos = header.right_top() + vec2(8.0, 0.0);
+        let buttons = [
+            ("⬆", "Move up", index > 0, index.saturating_sub(1)),
+            ("⬇", "Move down", index + 1 < file_count, index + 1),
+        ];
+        for (symbol, action, enabled, to) in buttons {
+            if !enabled || options.focused.is_some() {
+                continue;
+            }
+            let (rect, clicked) = paint_header_button(info, symbol, action, file, button_pos);
+            button_pos.x = rect.right() + 6.0;
+            if clicked {
+                moved = Some((index, to));
+            }
+        }
+        for (symbol, action, collapsed) in [
+            ("⊞", "Expand all of", false),
+            ("⊟", "Collapse all of", true),
+        ] {
+            let (rect, clicked) = paint_header_button(info, symbol, action, file, button_pos);
+            button_pos.x = rect.right() + 6.0;
+            if clicked {
+                file.inspector_collapsed = false;
+                for root in [&mut file.file_root, &mut file.ram_root] {
+                    // Keep the top level sections visible when collapsing.
+                    root.inspector_collapsed = false;
+                    for child in &mut root.children {
+                        set_collapsed(child, collapsed);
+                    }
+                }
+            }
+        }
+        if paint_header_button(info, "✕", "Close", file, button_pos).1 {
+            closed = Some(index);
+        }
 
         // draw on top of binary info background:
         info.painter.line_segment(
@@ -232,44 +2100,257 @@ fn ui_canvas(
                 pos2(info.canvas.min.x, line_y),
                 pos2(info.canvas.max.x, line_y),
             ],
-            Stroke::new(1.0, Rgba::from_white_alpha(0.5)),
+            Stroke::new(1.0, info.text_color.gamma_multiply(0.5)),
         );
 
         cursor_y += info.text_height;
 
-        let max_depth = 6;
         if !file.inspector_collapsed {
-            paint_scope(
-                info,
-                options,
-                0,
-                cursor_y,
-                &file.file_root,
-                file.file_root.bytes_start,
-                file.file_root.bytes_end,
-            );
-
-            cursor_y += max_depth as f32 * (options.rect_height + options.spacing);
+            let is_baseline = options
+                .baseline
+                .as_ref()
+                .is_some_and(|baseline| baseline.is_file(file));
+            let entropy = (options.color_by == ColorBy::Entropy).then(|| file.entropy());
+            for layout in [Layout::File, Layout::Ram] {
+                let root = match layout {
+                    Layout::File => &mut file.file_root,
+                    Layout::Ram => &mut file.ram_root,
+                };
+                let scope_file = ScopeFile {
+                    index,
+                    layout,
+                    data: &file.data,
+                    len: root.len(),
+                    is_baseline,
+                    entry_point: file.entry_point,
+                    entropy: entropy.as_deref(),
+                };
+
+                // When focused, the focused scope is drawn in place of the root, in the first row.
+                let mut path = vec![];
+                let mut root = root;
+                if options.focused.is_none() && !options.layout_view.shows(layout) {
+                    continue;
+                }
+                if let Some(focused) = &options.focused {
+                    if focused.layout != layout {
+                        continue;
+                    }
+                    match root.descendant_mut(&focused.children) {
+                        Some(node) => root = node,
+                        None => continue,
+                    }
+                    path = focused.children.clone();
+                }
+                let start = root.bytes_start;
+                let end = root.bytes_end;
+                let depth = if options.sorting.sort_by == SortBy::GroupedForSpaceUsageAnalysis {
+                    paint_grouped(
+                        info,
+                        options,
+                        &scope_file,
+                        &mut path,
+                        cursor_y,
+                        root,
+                        start,
+                        end,
+                    )
+                } else {
+                    paint_scope(
+                        info,
+                        options,
+                        &scope_file,
+                        &mut path,
+                        cursor_y,
+                        0,
+                        None,
+                        root,
+                        start,
+                        end,
+                    );
+                    visible_depth(options, root, 0)
+                };
+                cursor_y += depth as f32 * (options.rect_height + options.spacing);
+            }
+        }
+        cursor_y += info.text_height; // Extra spacing between binaries
 
-            paint_scope(
-                info,
-                options,
-                0,
-                cursor_y,
-                &file.ram_root,
-                file.ram_root.bytes_start,
-                file.ram_root.bytes_end,
+        let band = Rect::from_x_y_ranges(info.canvas.x_range(), line_y..=cursor_y);
+        let band_hovered = info
+            .response
+            .hover_pos()
+            .is_some_and(|pos| band.contains(pos));
+        if band_hovered {
+            info.painter.set(
+                where_to_put_band,
+                Shape::rect_filled(
+                    band,
+                    0.0,
+                    info.text_color.gamma_multiply(HOVERED_FILE_OPACITY),
+                ),
             );
-            cursor_y += max_depth as f32 * (options.rect_height + options.spacing);
         }
-        cursor_y += info.text_height; // Extra spacing between binaries
     }
 
+    options.file_headers = file_headers;
+    if let Some((from, to)) = moved {
+        move_file(options, files, from, to);
+    }
+    if let Some(index) = closed {
+        close_file(options, files, index);
+    }
     cursor_y
 }
 
+/// While a file header is dragged, show where the file will go, and move it there when released.
+fn drag_file_ui(options: &mut Options, info: &Info, files: &mut [ExecutableFile]) {
+    let Some(from) = options.dragged_file else {
+        return;
+    };
+    let Some(pointer) = info.ctx.input(|i| i.pointer.interact_pos()) else {
+        return;
+    };
+    // The number of other files whose headers are above the pointer is the new index of the file.
+    let to = options
+        .file_headers
+        .iter()
+        .filter(|(index, rect)| *index != from && rect.center().y < pointer.y)
+        .count();
+
+    if info.response.drag_released() || !info.response.dragged() {
+        options.dragged_file = None;
+        move_file(options, files, from, to);
+        return;
+    }
+
+    let stroke = Stroke::new(2.0, info.text_color);
+    info.painter.hline(info.canvas.x_range(), pointer.y, stroke);
+    if let Some(file) = files.get(from) {
+        info.painter.text(
+            pointer + vec2(8.0, -4.0),
+            Align2::LEFT_BOTTOM,
+            format!("Move {}", file.name),
+            info.font_id.clone(),
+            info.text_color,
+        );
+    }
+}
+
+/// Moves the file at index `from` to index `to`, shifting the files in between, and updates the indexes in `options`.
+fn move_file(options: &mut Options, files: &mut [ExecutableFile], from: usize, to: usize) {
+    if from >= files.len() || to >= files.len() || from == to {
+        return;
+    }
+    if to < from {
+        files[to..=from].rotate_right(1);
+    } else {
+        files[from..=to].rotate_left(1);
+    }
+    let new_index = |index: usize| {
+        if index == from {
+            to
+        } else if from < index && index <= to {
+            index - 1
+        } else if to <= index && index < from {
+            index + 1
+        } else {
+            index
+        }
+    };
+    for path in [
+        &mut options.selected,
+        &mut options.focused,
+        &mut options.context_menu_scope,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        path.file = new_index(path.file);
+    }
+}
+
+/// Removes the file at `index` and updates the indexes in `options`, forgetting anything that was in the removed file.
+/// The baseline is kept as it holds its own copy of the file.
+fn close_file(options: &mut Options, files: &mut Vec<ExecutableFile>, index: usize) {
+    if index >= files.len() {
+        return;
+    }
+    files.remove(index);
+    if options
+        .focused
+        .as_ref()
+        .is_some_and(|focused| focused.file == index)
+    {
+        options.focused = None;
+        options.canvas_width_bytes = 0.0;
+        options.sideways_pan_in_points = 0.0;
+    }
+    for path in [
+        &mut options.selected,
+        &mut options.focused,
+        &mut options.context_menu_scope,
+    ] {
+        match path {
+            Some(p) if p.file == index => *path = None,
+            Some(p) if p.file > index => p.file -= 1,
+            _ => {}
+        }
+    }
+    options.dragged_file = None;
+    // Rebuilt when the files are painted next frame.
+    options.file_headers.clear();
+}
+
+/// Collapses or expands `node` and everything in it.
+fn set_collapsed(node: &mut FileNode, collapsed: bool) {
+    node.inspector_collapsed = collapsed && !node.children.is_empty();
+    for child in &mut node.children {
+        set_collapsed(child, collapsed);
+    }
+}
+
+/// False if the children of a scope drawn `row` rows below the first would be deeper than [`Options::max_depth`].
+fn shows_children_at(options: &Options, row: usize) -> bool {
+    match options.max_depth {
+        Some(max_depth) => row + 1 < max_depth,
+        None => true,
+    }
+}
+
+/// How many rows are needed to draw this node and its children, drawn `row` rows below the first.
+/// Matches what [`paint_scope`] draws: collapsed children, children hidden by [`Options::hide_filtered`]
+/// and rows beyond [`Options::max_depth`] take no space.
+fn visible_depth(options: &Options, section: &FileNode, row: usize) -> usize {
+    if section.inspector_collapsed || !shows_children_at(options, row) {
+        1
+    } else {
+        1 + section
+            .children
+            .iter()
+            .filter(|child| !options.hide_filtered || passes_type_filter(options, child))
+            .map(|child| visible_depth(options, child, row + 1))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
 fn interact_with_canvas(options: &mut Options, response: &Response, info: &Info) {
-    if response.drag_delta().x != 0.0 {
+    // Set again by the scope under the pointer as it is painted.
+    if response.secondary_clicked() {
+        options.context_menu_scope = None;
+    }
+
+    if response.drag_started_by(PointerButton::Primary) && options.focused.is_none() {
+        let origin = info.ctx.input(|i| i.pointer.press_origin());
+        options.dragged_file = options
+            .file_headers
+            .iter()
+            .find(|(_, rect)| origin.is_some_and(|origin| rect.contains(origin)))
+            .map(|(index, _)| *index);
+    }
+
+    // Dragging a file header moves the file instead of panning.
+    if options.dragged_file.is_none() && response.drag_delta().x != 0.0 {
         options.sideways_pan_in_points += response.drag_delta().x;
         options.zoom_to_relative_bytes_range = None;
     }
@@ -286,9 +2367,16 @@ fn interact_with_canvas(options: &mut Options, response: &Response, info: &Info)
         if response.dragged_by(PointerButton::Secondary) {
             zoom_factor *= (response.drag_delta().y * 0.01).exp();
         }
+        if !zoom_factor.is_finite() || zoom_factor <= 0.0 {
+            zoom_factor = 1.0;
+        }
 
         if zoom_factor != 1.0 {
-            options.canvas_width_bytes /= zoom_factor;
+            // Stop at the zoom limits, keeping the pan in step with the zoom that actually happened.
+            let canvas_width_bytes = (options.canvas_width_bytes / zoom_factor)
+                .clamp(MIN_CANVAS_WIDTH_BYTES, max_canvas_width_bytes(info));
+            zoom_factor = options.canvas_width_bytes / canvas_width_bytes;
+            options.canvas_width_bytes = canvas_width_bytes;
 
             if let Some(mouse_pos) = response.hover_pos() {
                 let zoom_center = mouse_pos.x - info.canvas.min.x;
@@ -313,7 +2401,9 @@ fn interact_with_canvas(options: &mut Options, response: &Response, info: &Info)
 
         let canvas_width = response.rect.width();
 
-        let target_canvas_width_bytes = (end_bytes - start_bytes) as f32;
+        // Zooming to an empty section would divide by zero.
+        let target_canvas_width_bytes =
+            ((end_bytes - start_bytes) as f32).max(MIN_CANVAS_WIDTH_BYTES);
         let target_pan_in_points = -canvas_width * start_bytes as f32 / target_canvas_width_bytes;
 
         options.canvas_width_bytes = lerp(
@@ -330,28 +2420,64 @@ fn interact_with_canvas(options: &mut Options, response: &Response, info: &Info)
 
         info.ctx.request_repaint();
     }
+
+    // Recover from anything that slipped through, e.g. options saved by an older version, rather than drawing garbage.
+    if !options.canvas_width_bytes.is_finite() || !options.sideways_pan_in_points.is_finite() {
+        options.canvas_width_bytes = (info.stop_bytes - info.start_bytes) as f32;
+        options.sideways_pan_in_points = 0.0;
+        options.zoom_to_relative_bytes_range = None;
+    }
+    options.canvas_width_bytes = options
+        .canvas_width_bytes
+        .clamp(MIN_CANVAS_WIDTH_BYTES, max_canvas_width_bytes(info));
+}
+
+/// The most bytes the width of the canvas can span, see [`MAX_ZOOM_OUT`].
+fn max_canvas_width_bytes(info: &Info) -> f32 {
+    ((info.stop_bytes - info.start_bytes) as f32 * MAX_ZOOM_OUT).max(MIN_CANVAS_WIDTH_BYTES)
+}
+
+/// Where the last byte of the widest shown layout of `files` is, the end of the timeline when zoomed all the way out.
+fn max_bytes(options: &Options, files: &[ExecutableFile]) -> BytesCount {
+    files
+        .iter()
+        .flat_map(|file| {
+            [Layout::File, Layout::Ram]
+                .into_iter()
+                .filter(|layout| options.layout_view.shows(*layout))
+                .map(|layout| file.root(layout).bytes_end)
+        })
+        .max()
+        .unwrap_or(100)
 }
 
+/// Returns the grid lines spanning `canvas` and the labels along the top and bottom of `label_rect`.
+/// With `addresses` the labels are hexadecimal addresses rather than sizes.
 fn paint_timeline(
     info: &Info,
     canvas: Rect,
+    label_rect: Rect,
     options: &Options,
     start_bytes: BytesCount,
-) -> Vec<egui::Shape> {
+    addresses: bool,
+) -> (Vec<egui::Shape>, Vec<egui::Shape>) {
     let mut shapes = vec![];
+    let mut labels = vec![];
 
     if options.canvas_width_bytes <= 0.0 {
-        return shapes;
+        return (shapes, labels);
     }
 
     let alpha_multiplier = 0.3;
 
-    // We show all measurements relative to start_bytes
+    // We show all measurements relative to start_bytes, which is not necessarily the start of the file.
 
+    let numbers = Numbers::of(options);
+    let base = numbers.grid_base(addresses);
     let max_lines = canvas.width() / 4.0;
-    let mut grid_spacing_bytes = 1;
+    let mut grid_spacing_bytes: i64 = 1;
     while options.canvas_width_bytes / (grid_spacing_bytes as f32) > max_lines {
-        grid_spacing_bytes *= 10;
+        grid_spacing_bytes *= base;
     }
 
     // We fade in lines as we zoom in:
@@ -362,17 +2488,22 @@ fn paint_timeline(
     let medium_alpha = remap_clamp(zoom_factor, 0.0..=1.0, 0.1..=0.5);
     let tiny_alpha = remap_clamp(zoom_factor, 0.0..=1.0, 0.0..=0.1);
 
-    let mut grid_bytes = 0;
+    // Start at the first line visible on the left of the canvas, but never before the start of the file.
+    let file_start = info.start_bytes as i64 - start_bytes as i64;
+    let left_bytes = (info.bytes_from_point(options, canvas.min.x) - start_bytes as f64) as i64;
+    let mut grid_bytes = left_bytes.max(file_start);
+    grid_bytes = (grid_bytes as f64 / grid_spacing_bytes as f64).ceil() as i64 * grid_spacing_bytes;
 
     loop {
-        let line_x = info.point_from_bytes(options, start_bytes + grid_bytes);
+        let line_x =
+            info.point_from_bytes(options, (start_bytes as i64 + grid_bytes) as BytesCount);
         if line_x > canvas.max.x {
             break;
         }
 
         if canvas.min.x <= line_x {
-            let big_line = grid_bytes % (grid_spacing_bytes * 100) == 0;
-            let medium_line = grid_bytes % (grid_spacing_bytes * 10) == 0;
+            let big_line = grid_bytes % (grid_spacing_bytes * base * base) == 0;
+            let medium_line = grid_bytes % (grid_spacing_bytes * base) == 0;
 
             let line_alpha = if big_line {
                 big_alpha
@@ -384,7 +2515,11 @@ fn paint_timeline(
 
             shapes.push(egui::Shape::line_segment(
                 [pos2(line_x, canvas.min.y), pos2(line_x, canvas.max.y)],
-                Stroke::new(1.0, Rgba::from_white_alpha(line_alpha * alpha_multiplier)),
+                Stroke::new(
+                    1.0,
+                    info.text_color
+                        .gamma_multiply(line_alpha * alpha_multiplier),
+                ),
             ));
 
             let text_alpha = if big_line {
@@ -396,15 +2531,24 @@ fn paint_timeline(
             };
 
             if text_alpha > 0.0 {
-                let text = grid_text(grid_bytes);
+                let text = if addresses && start_bytes == info.start_bytes {
+                    numbers.offset((start_bytes as i64 + grid_bytes) as u64)
+                } else if addresses {
+                    // Relative to the selection.
+                    numbers.delta(grid_bytes)
+                } else if grid_bytes < 0 {
+                    format!("-{}", numbers.grid(grid_bytes.unsigned_abs()))
+                } else {
+                    numbers.grid(grid_bytes as u64)
+                };
                 let text_x = line_x + 4.0;
-                let text_color = Rgba::from_white_alpha((text_alpha * 2.0).min(1.0)).into();
+                let text_color = info.text_color.gamma_multiply((text_alpha * 2.0).min(1.0));
 
                 info.painter.fonts(|f| {
                     // Text at top:
-                    shapes.push(egui::Shape::text(
+                    labels.push(egui::Shape::text(
                         f,
-                        pos2(text_x, canvas.min.y),
+                        pos2(text_x, label_rect.min.y),
                         Align2::LEFT_TOP,
                         &text,
                         info.font_id.clone(),
@@ -414,9 +2558,9 @@ fn paint_timeline(
 
                 info.painter.fonts(|f| {
                     // Text at bottom:
-                    shapes.push(egui::Shape::text(
+                    labels.push(egui::Shape::text(
                         f,
-                        pos2(text_x, canvas.max.y - info.text_height),
+                        pos2(text_x, label_rect.max.y - info.text_height),
                         Align2::LEFT_TOP,
                         &text,
                         info.font_id.clone(),
@@ -429,40 +2573,21 @@ fn paint_timeline(
         grid_bytes += grid_spacing_bytes;
     }
 
-    shapes
-}
-
-fn grid_text(bytes: u64) -> String {
-    if bytes >= 1_000_000 {
-        let mb = bytes as f32 / 1_000_000f32;
-        format!("{mb} MB")
-    } else if bytes >= 1_000 {
-        let kb = bytes as f32 / 1_000f32;
-        format!("{kb} KB")
-    } else {
-        format!("{bytes:.3} bytes")
-    }
+    (shapes, labels)
 }
 
 #[allow(clippy::too_many_arguments)]
+/// `bytes_start..bytes_end` is where on the timeline the section is drawn, see [`paint_scope`].
 fn paint_record(
     info: &Info,
     options: &mut Options,
+    file: &ScopeFile,
+    path: &[usize],
     top_y: f32,
-    section: &FileNode,
-    unscaled_start: u64,
-    unscaled_end: u64,
+    section: &mut FileNode,
+    bytes_start: u64,
+    bytes_end: u64,
 ) -> PaintResult {
-    let bytes_start = if options.to_scale {
-        section.bytes_start
-    } else {
-        unscaled_start
-    };
-    let bytes_end = if options.to_scale {
-        section.bytes_end
-    } else {
-        unscaled_end
-    };
     let start_x = info.point_from_bytes(options, bytes_start);
     let stop_x = info.point_from_bytes(options, bytes_end);
     if info.canvas.max.x < start_x
@@ -475,6 +2600,7 @@ fn paint_record(
     let bottom_y = top_y + options.rect_height;
 
     let rect = Rect::from_min_max(pos2(start_x, top_y), pos2(stop_x, bottom_y));
+    info.mark_painted(rect);
 
     let is_hovered = if let Some(mouse_pos) = info.response.hover_pos() {
         rect.contains(mouse_pos)
@@ -482,23 +2608,72 @@ fn paint_record(
         false
     };
 
+    let wide_enough_for_text = stop_x - start_x > 32.0;
+    let has_disclosure = wide_enough_for_text && !section.children.is_empty();
+
     if is_hovered && info.response.clicked() {
-        options.zoom_to_relative_bytes_range = Some((
-            info.ctx.input(|i| i.time),
-            (
-                section.bytes_start - info.start_bytes,
-                section.bytes_end - info.start_bytes,
-            ),
-        ));
+        let disclosure_clicked = has_disclosure
+            && info
+                .response
+                .hover_pos()
+                .is_some_and(|pos| pos.x < start_x + DISCLOSURE_WIDTH);
+        if disclosure_clicked {
+            section.inspector_collapsed = !section.inspector_collapsed;
+        } else {
+            let node_path = NodePath {
+                file: file.index,
+                layout: file.layout,
+                children: path.to_vec(),
+            };
+            // Where the scope is drawn, which differs from its offsets when not to scale or grouped.
+            let zoom_to_section = Some((
+                info.ctx.input(|i| i.time),
+                (bytes_start - info.start_bytes, bytes_end - info.start_bytes),
+            ));
+            match (options.click_action, info.response.double_clicked()) {
+                (ClickAction::Zoom, true) => {
+                    options.focused = Some(node_path);
+                    options.zoom_to_relative_bytes_range = zoom_to_section;
+                }
+                (ClickAction::Zoom, false) | (ClickAction::Select, true) => {
+                    options.selected = Some(node_path);
+                    options.zoom_to_relative_bytes_range = zoom_to_section;
+                }
+                (ClickAction::Select, false) => options.selected = Some(node_path),
+            }
+        }
     }
 
+    let filtered_out = is_filtered_out(options, &info.search, path, section);
+    let diff = if options.color_by_baseline {
+        options.compare_to_baseline(file, section)
+    } else {
+        None
+    };
     let rect_color = if is_hovered {
         HOVER_COLOR
     } else {
-        color_from_size(section.bytes_end - section.bytes_start)
+        scope_color(options, &info.size_range, file, section, diff)
     };
+    let alpha = if filtered_out { FILTERED_ALPHA } else { 1.0 };
+    let rect_color = rect_color * alpha;
+    let text_color = text_color_on(rect_color, info.canvas_color.into()).gamma_multiply(alpha);
 
     let min_width = options.min_width;
+    let numbers = Numbers::of(options);
+    // Sections spanning fewer windows are filled with the entropy of their own bytes,
+    // the windows would mix in the bytes around them.
+    let entropy_strip = file.entropy.filter(|_| {
+        !is_hovered
+            && diff.is_none()
+            && !matches!(section.ty, SectionType::Gap)
+            && section.category() != SectionTypeCategory::Bss
+            && section
+                .file_bytes_end
+                .saturating_sub(section.file_bytes_start)
+                >= 2 * WINDOW as u64
+            && rect.width() > ENTROPY_STRIP_STEP
+    });
 
     if rect.width() <= min_width {
         // faster to draw it as a thin line
@@ -506,27 +2681,103 @@ fn paint_record(
             [rect.center_top(), rect.center_bottom()],
             egui::Stroke::new(min_width, rect_color),
         );
+    } else if let Some(entropy) = entropy_strip {
+        paint_entropy_strip(info, entropy, section, rect, alpha);
     } else {
         info.painter.rect_filled(rect, options.rounding, rect_color);
     }
 
-    let wide_enough_for_text = stop_x - start_x > 32.0;
+    let is_selected = options
+        .selected
+        .as_ref()
+        .is_some_and(|selected| selected.is(file, path));
+
+    if wide_enough_for_text {
+        info.mark_accessible(
+            Id::new(("scope", file.index, file.layout, path)),
+            rect,
+            WidgetInfo::selected(
+                WidgetType::SelectableLabel,
+                is_selected,
+                format!(
+                    "{}, {}, at {}",
+                    section.name,
+                    numbers.size(section.len()),
+                    numbers.offset(section.bytes_start)
+                ),
+            ),
+        );
+    }
+    if is_selected {
+        info.painter
+            .rect_stroke(rect, options.rounding, Stroke::new(2.0, Color32::WHITE));
+    } else if section.overlaps_sibling() {
+        info.painter
+            .rect_stroke(rect, options.rounding, Stroke::new(2.0, OVERLAP_COLOR));
+    }
+    if file.layout == Layout::Ram {
+        if let Some(entry_point) = file.entry_point_in(section) {
+            // Placed proportionally within the rect so it also works when not to scale.
+            let fraction = (entry_point - section.bytes_start) as f32 / section.len() as f32;
+            let x = lerp(start_x..=stop_x, fraction);
+            let size = 0.4 * options.rect_height;
+            info.painter.add(Shape::convex_polygon(
+                vec![
+                    pos2(x - size, top_y),
+                    pos2(x + size, top_y),
+                    pos2(x, top_y + size),
+                ],
+                ENTRY_POINT_COLOR,
+                Stroke::NONE,
+            ));
+        }
+    }
+    if let Some(links) = &info.links {
+        if is_selected {
+            links.selected_rect.set(Some(rect));
+        }
+        links.mark_painted(file, section, rect);
+    }
+
     if wide_enough_for_text {
         let painter = info.painter.with_clip_rect(rect.intersect(info.canvas));
 
-        let text = &section.name;
+        let mut text = if has_disclosure {
+            let symbol = if section.inspector_collapsed {
+                "⏵"
+            } else {
+                "⏷"
+            };
+            format!("{symbol} {}", section.name)
+        } else {
+            section.name.clone()
+        };
+        if options.label_sizes {
+            let text_with_size = format!("{text} ({})", numbers.size(section.len()));
+            let width = info.ctx.fonts(|f| {
+                f.layout_no_wrap(
+                    text_with_size.clone(),
+                    info.font_id.clone(),
+                    Color32::PLACEHOLDER,
+                )
+                .size()
+                .x
+            });
+            if width + 8.0 <= stop_x - start_x {
+                text = text_with_size;
+            }
+        }
         let pos = pos2(
             start_x + 4.0,
             top_y + 0.5 * (options.rect_height - info.text_height),
         );
         let pos = painter.round_pos_to_pixels(pos);
-        const TEXT_COLOR: Color32 = Color32::BLACK;
         painter.text(
             pos,
             Align2::LEFT_TOP,
             text,
             info.font_id.clone(),
-            TEXT_COLOR,
+            text_color,
         );
     }
 
@@ -537,55 +2788,744 @@ fn paint_record(
     }
 }
 
-// TODO: would make more sense to color by section type
-fn color_from_size(bytes: BytesCount) -> Rgba {
-    let kb = bytes as f32 / 1000.0;
+/// How numbers are written, see [`Options::hex_offsets`] and [`Options::binary_units`].
+#[derive(Clone, Copy)]
+struct Numbers {
+    hex_offsets: bool,
+    binary_units: bool,
+}
+
+impl Numbers {
+    fn of(options: &Options) -> Self {
+        Numbers {
+            hex_offsets: options.hex_offsets,
+            binary_units: options.binary_units,
+        }
+    }
+
+    /// An offset or address, e.g. `0x1a0` or `416`.
+    fn offset(self, offset: u64) -> String {
+        if self.hex_offsets {
+            format!("0x{offset:x}")
+        } else {
+            offset.to_string()
+        }
+    }
+
+    /// A change in offset or size with its sign, e.g. `+0x1a0` or `-32`.
+    fn delta(self, delta: i64) -> String {
+        let sign = if delta < 0 { "-" } else { "+" };
+        format!("{sign}{}", self.offset(delta.unsigned_abs()))
+    }
+
+    /// `bytes` in the largest unit it is at least one of, e.g. `(1.5, "KB")`, or `None` for under a kilobyte.
+    fn scaled(self, bytes: u64) -> Option<(f64, &'static str)> {
+        let (kilo, units) = if self.binary_units {
+            (1024u64, ["KiB", "MiB", "GiB"])
+        } else {
+            (1000, ["KB", "MB", "GB"])
+        };
+        let mut unit_size = 1;
+        let mut scaled = None;
+        for unit in units {
+            unit_size *= kilo;
+            if bytes < unit_size {
+                break;
+            }
+            scaled = Some((bytes as f64 / unit_size as f64, unit));
+        }
+        scaled
+    }
+
+    /// Human readable size, e.g. `1.2 MB` or `1.2 KiB`
+    fn size(self, bytes: u64) -> String {
+        match self.scaled(bytes) {
+            Some((count, unit)) => format!("{count:.1} {unit}"),
+            None => format!("{bytes} bytes"),
+        }
+    }
+
+    /// A size on the timeline grid, e.g. `1.25 MB`.
+    /// Grid lines fall on round numbers, so the size is rounded to a few decimals and trailing zeros are dropped.
+    fn grid(self, bytes: u64) -> String {
+        match self.scaled(bytes) {
+            Some((count, unit)) => {
+                let count = format!("{count:.3}");
+                let count = count.trim_end_matches('0').trim_end_matches('.');
+                format!("{count} {unit}")
+            }
+            None => format!("{bytes} bytes"),
+        }
+    }
+
+    /// Grid lines are this many times further apart at each step of zooming out,
+    /// so that they fall on round numbers as they are written.
+    fn grid_base(self, addresses: bool) -> i64 {
+        let round_in_hex = if addresses {
+            self.hex_offsets
+        } else {
+            self.binary_units
+        };
+        if round_in_hex {
+            16
+        } else {
+            10
+        }
+    }
+}
+
+/// Black or white, whichever is easier to read on `color` painted over `background`.
+/// `color` may be translucent, e.g. when filtered out, so the same scope can need different text in the light and dark themes.
+fn text_color_on(color: Rgba, background: Rgba) -> Color32 {
+    let blended = color + background * (1.0 - color.a());
+    // Relative luminance as defined by WCAG, the cutoff is where black and white text have the same contrast.
+    let luminance = 0.2126 * blended.r() + 0.7152 * blended.g() + 0.0722 * blended.b();
+    if luminance > 0.18 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
+
+/// Mixes a little of a hue picked by `index` into `color`, the first file is left as is.
+fn tint_for_file(color: Rgba, index: usize) -> Rgba {
+    const TINT_AMOUNT: f32 = 0.2;
+    if index == 0 {
+        return color;
+    }
+    // Stepping by the golden ratio keeps neighbouring files far apart on the color wheel.
+    let hue = (index as f32 * 0.618_034).fract();
+    let tint = Rgba::from(ecolor::Hsva::new(hue, 0.8, 0.9, 1.0)) * color.a();
+    lerp(color..=tint, TINT_AMOUNT)
+}
+
+/// Red for growth, green for shrinking, brighter the larger the change.
+fn color_from_diff(diff: NodeDiff) -> Rgba {
+    match diff {
+        NodeDiff::Added => Rgba::from_rgb(0.2, 0.4, 0.9),
+        NodeDiff::Identical => Rgba::from_gray(0.3),
+        NodeDiff::Changed { delta: 0 } => Rgba::from_rgb(0.8, 0.7, 0.1),
+        NodeDiff::Changed { delta } => {
+            let kb = delta.unsigned_abs() as f32 / 1000.0;
+            let intensity = remap_clamp(kb, 0.0..=33.0, 0.4..=1.0);
+            if delta > 0 {
+                Rgba::from_rgb(intensity, 0.1, 0.1)
+            } else {
+                Rgba::from_rgb(0.1, intensity, 0.1)
+            }
+        }
+    }
+}
+
+/// `fraction` is where the size lies in [`Info::size_range`], so the full gradient is used whatever the size of the files.
+fn color_from_size(fraction: f32) -> Rgba {
     // Brighter = larger
     // So we start with dark colors (blue) and later bright colors (green).
-    let b = remap_clamp(kb, 0.0..=5.0, 1.0..=0.3);
-    let r = remap_clamp(kb, 0.0..=10.0, 0.5..=0.8);
-    let g = remap_clamp(kb, 10.0..=33.0, 0.1..=0.8);
+    let b = remap_clamp(fraction, 0.0..=0.15, 1.0..=0.3);
+    let r = remap_clamp(fraction, 0.0..=0.3, 0.5..=0.8);
+    let g = remap_clamp(fraction, 0.3..=1.0, 0.1..=0.8);
     let a = 0.9;
     Rgba::from_rgb(r, g, b) * a
 }
 
+/// Blue for repetitive bytes, through purple, to red for [`MAX_BITS_PER_BYTE`].
+fn color_from_entropy(bits_per_byte: f32) -> Rgba {
+    let fraction = bits_per_byte / MAX_BITS_PER_BYTE;
+    let r = remap_clamp(fraction, 0.0..=1.0, 0.1..=0.95);
+    let b = remap_clamp(fraction, 0.0..=1.0, 0.9..=0.1);
+    let a = 0.9;
+    Rgba::from_rgb(r, 0.15, b) * a
+}
+
+/// Fill `rect` with columns colored by the entropy of the part of `section` each covers.
+/// The file range of `section` is spread over the whole rect, so this also works when not drawn to scale.
+fn paint_entropy_strip(
+    info: &Info,
+    entropy: &EntropyProfile,
+    section: &FileNode,
+    rect: Rect,
+    alpha: f32,
+) {
+    // Columns outside of the canvas would be clipped anyway.
+    let visible = rect.x_range().intersection(info.canvas.x_range());
+    let file_len = section.file_bytes_end - section.file_bytes_start;
+    let bytes_at = |x: f32| {
+        let fraction = remap_clamp(x, rect.x_range(), 0.0..=1.0) as f64;
+        section.file_bytes_start + (fraction * file_len as f64) as u64
+    };
+    let mut left = visible.min;
+    while left < visible.max {
+        let right = (left + ENTROPY_STRIP_STEP).min(visible.max);
+        let bits = entropy
+            .range(bytes_at(left), bytes_at(right).max(bytes_at(left) + 1))
+            .filter(|_| file_len != 0)
+            .unwrap_or(0.0);
+        info.painter.rect_filled(
+            Rect::from_x_y_ranges(left..=right, rect.y_range()),
+            0.0,
+            color_from_entropy(bits) * alpha,
+        );
+        left = right;
+    }
+}
+
+fn color_from_type(ty: &SectionType, name: &str) -> Rgba {
+    color_from_category(ty.category(name))
+}
+
+/// A distinct hue per [`SectionTypeCategory`], at the same brightness as [`color_from_size`].
+fn color_from_category(category: SectionTypeCategory) -> Rgba {
+    let (r, g, b) = match category {
+        SectionTypeCategory::Headers => (0.5, 0.5, 0.5),
+        SectionTypeCategory::Code => (0.9, 0.35, 0.3),
+        SectionTypeCategory::ReadOnlyData => (0.3, 0.5, 0.9),
+        SectionTypeCategory::Data => (0.3, 0.75, 0.4),
+        SectionTypeCategory::Bss => (0.6, 0.85, 0.8),
+        SectionTypeCategory::Symbols => (0.9, 0.7, 0.25),
+        SectionTypeCategory::Relocations => (0.7, 0.45, 0.25),
+        SectionTypeCategory::Debug => (0.65, 0.4, 0.85),
+        SectionTypeCategory::Other => (0.35, 0.35, 0.45),
+    };
+    Rgba::from_rgb(r, g, b) * 0.9
+}
+
+/// `path` is the path from the root of the tree to `section`, see [`NodePath::children`].
+/// `min_y` is the top of the first row and `section` is drawn `row` rows below it.
+/// `parent_len` is the length of the parent of `section`, `None` for the root.
+/// `bytes_start..bytes_end` is where on the timeline `section` is drawn.
+/// When [`Options::to_scale`] is on this is the real range of `section`, otherwise it is its even share of the parent's range.
+#[allow(clippy::too_many_arguments)]
 fn paint_scope(
     info: &Info,
     options: &mut Options,
-    depth: usize,
+    file: &ScopeFile,
+    path: &mut Vec<usize>,
     min_y: f32,
-    section: &FileNode,
-    unscaled_start: u64,
-    unscaled_end: u64,
+    row: usize,
+    parent_len: Option<u64>,
+    section: &mut FileNode,
+    bytes_start: u64,
+    bytes_end: u64,
 ) -> PaintResult {
-    let top_y = min_y + (depth as f32) * (options.rect_height + options.spacing);
-
-    let result = paint_record(info, options, top_y, section, unscaled_start, unscaled_end);
-
-    if result != PaintResult::Culled {
-        for (i, child) in section.children.iter().enumerate() {
-            let width = (unscaled_end - unscaled_start) / section.children.len() as u64;
+    let top_y = min_y + (row as f32) * (options.rect_height + options.spacing);
+
+    let result = paint_record(
+        info,
+        options,
+        file,
+        path,
+        top_y,
+        section,
+        bytes_start,
+        bytes_end,
+    );
+
+    if result != PaintResult::Culled
+        && !section.inspector_collapsed
+        && shows_children_at(options, row)
+    {
+        let len = section.len();
+        let shown: Vec<usize> = (0..section.children.len())
+            .filter(|&i| {
+                !options.hide_filtered || passes_type_filter(options, &section.children[i])
+            })
+            .collect();
+        // When not to scale the remaining children share the space of the hidden ones.
+        let width = (bytes_end - bytes_start) / shown.len().max(1) as u64;
+        for (slot, i) in shown.into_iter().enumerate() {
+            let child = &section.children[i];
+            let (child_start, child_end) = if options.to_scale {
+                (child.bytes_start, child.bytes_end)
+            } else {
+                (
+                    bytes_start + slot as u64 * width,
+                    bytes_start + (slot as u64 + 1) * width,
+                )
+            };
+            path.push(i);
             paint_scope(
                 info,
                 options,
-                depth + 1,
+                file,
+                path,
                 min_y,
-                child,
-                section.bytes_start + i as u64 * width,
-                section.bytes_start + (i as u64 + 1) * width,
+                row + 1,
+                Some(len),
+                &mut section.children[i],
+                child_start,
+                child_end,
             );
+            path.pop();
         }
+    }
 
-        if result == PaintResult::Hovered {
-            egui::show_tooltip_at_pointer(&info.ctx, Id::new("inspector_tooltip"), |ui| {
-                paint_section_details(ui, section);
+    show_scope_details(info, options, file, path, parent_len, section, result);
+    result
+}
+
+/// Opens the context menu of a hovered scope on a secondary click and describes it in a tooltip.
+fn show_scope_details(
+    info: &Info,
+    options: &mut Options,
+    file: &ScopeFile,
+    path: &[usize],
+    parent_len: Option<u64>,
+    section: &FileNode,
+    result: PaintResult,
+) {
+    if result == PaintResult::Hovered {
+        if info.response.secondary_clicked() {
+            options.context_menu_scope = Some(NodePath {
+                file: file.index,
+                layout: file.layout,
+                children: path.to_vec(),
             });
         }
+        egui::show_tooltip_at_pointer(&info.ctx, Id::new("inspector_tooltip"), |ui| {
+            let diff = options.compare_to_baseline(file, section);
+            paint_section_details(ui, Numbers::of(options), file, parent_len, diff, section);
+        });
     }
-    result
 }
 
-fn paint_section_details(ui: &mut Ui, section: &FileNode) {
+/// Sections below a scope that share a name, drawn as one block by [`paint_grouped`].
+#[derive(Clone, Debug)]
+struct NameGroup {
+    name: String,
+    /// The type of the first of the sections, for coloring the block.
+    ty: SectionType,
+    /// The combined length of the sections.
+    len: u64,
+    /// The largest first.
+    sections: Vec<GroupedSection>,
+}
+
+#[derive(Clone, Debug)]
+struct GroupedSection {
+    /// From the scope that is grouped to the section.
+    path: Vec<usize>,
+    len: u64,
+    parent_len: u64,
+}
+
+/// The [`NameGroup`]s of each scope drawn grouped, see [`name_groups`].
+/// Kept until the files change or [`Options::hide_filtered`] hides other sections.
+#[derive(Clone, Debug, Default)]
+struct NameGroups {
+    key: Option<(FilesKey, Option<Vec<bool>>)>,
+    groups: Vec<(NodePath, Arc<[NameGroup]>)>,
+}
+
+impl NameGroups {
+    fn keep_if_unchanged(
+        &mut self,
+        hide_filtered: bool,
+        type_filter: &HashSet<SectionTypeCategory>,
+        files: &[ExecutableFile],
+    ) {
+        let shown = hide_filtered.then(|| {
+            SectionTypeCategory::ALL
+                .iter()
+                .map(|category| type_filter.contains(category))
+                .collect()
+        });
+        let key = Some((FilesKey::of(files), shown));
+        if self.key != key {
+            self.key = key;
+            self.groups.clear();
+        }
+    }
+}
+
+/// The sections below `scope` at `path` merged by name for [`SortBy::GroupedForSpaceUsageAnalysis`],
+/// the name with the largest combined size first.
+/// The sections are those without children of their own, where a table counts as a whole,
+/// see [`FileNode::has_entries`].
+fn name_groups(options: &mut Options, path: &NodePath, scope: &FileNode) -> Arc<[NameGroup]> {
+    if let Some((_, groups)) = options.name_groups.groups.iter().find(|(p, _)| p == path) {
+        return groups.clone();
+    }
+
+    let mut groups: Vec<NameGroup> = vec![];
+    let mut by_name: HashMap<&str, usize> = HashMap::new();
+    for_each_leaf(scope, &mut vec![], &mut |path, parent, section| {
+        if options.hide_filtered && !passes_type_filter(options, section) {
+            return;
+        }
+        let index = *by_name.entry(&section.name).or_insert_with(|| {
+            groups.push(NameGroup {
+                name: section.name.clone(),
+                ty: section.ty.clone(),
+                len: 0,
+                sections: vec![],
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[index];
+        group.len += section.len();
+        group.sections.push(GroupedSection {
+            path: path.to_vec(),
+            len: section.len(),
+            parent_len: parent.len(),
+        });
+    });
+    for group in &mut groups {
+        group
+            .sections
+            .sort_by_key(|section| std::cmp::Reverse(section.len));
+    }
+    groups.sort_by(|a, b| b.len.cmp(&a.len).then_with(|| a.name.cmp(&b.name)));
+
+    let groups: Arc<[NameGroup]> = groups.into();
+    options
+        .name_groups
+        .groups
+        .push((path.clone(), groups.clone()));
+    groups
+}
+
+/// Calls `visit` with the path, parent and node of each section below `node` without children of its own,
+/// where a table counts as a whole, see [`FileNode::has_entries`]. Gaps and empty sections are skipped.
+/// `path` leads to `node` and is left as it was.
+fn for_each_leaf<'a>(
+    node: &'a FileNode,
+    path: &mut Vec<usize>,
+    visit: &mut impl FnMut(&[usize], &'a FileNode, &'a FileNode),
+) {
+    for (i, child) in node.children.iter().enumerate() {
+        path.push(i);
+        if !child.children.is_empty() && !child.has_entries() {
+            for_each_leaf(child, path, visit);
+        } else if child.len() > 0 && !matches!(child.ty, SectionType::Gap) {
+            visit(path, node, child);
+        }
+        path.pop();
+    }
+}
+
+/// Where scopes of lengths `lens` are drawn side by side from `bytes_start`:
+/// one after another at their lengths when [`Options::to_scale`] is on, otherwise an even share of the range each.
+fn packed_ranges(
+    options: &Options,
+    lens: impl ExactSizeIterator<Item = u64>,
+    bytes_start: u64,
+    bytes_end: u64,
+) -> Vec<(u64, u64)> {
+    let width = (bytes_end - bytes_start) / lens.len().max(1) as u64;
+    let mut start = bytes_start;
+    lens.map(|len| {
+        let len = if options.to_scale { len } else { width };
+        start += len;
+        (start - len, start)
+    })
+    .collect()
+}
+
+/// Draws `scope` for [`SortBy::GroupedForSpaceUsageAnalysis`] in place of [`paint_scope`]:
+/// below it a block for each [`NameGroup`] and below each block its sections with their children.
+/// `path` leads to `scope`, which is drawn at `top_y`. Returns how many rows were needed.
+#[allow(clippy::too_many_arguments)]
+fn paint_grouped(
+    info: &Info,
+    options: &mut Options,
+    file: &ScopeFile,
+    path: &mut Vec<usize>,
+    top_y: f32,
+    scope: &mut FileNode,
+    bytes_start: u64,
+    bytes_end: u64,
+) -> usize {
+    let result = paint_record(
+        info,
+        options,
+        file,
+        path,
+        top_y,
+        scope,
+        bytes_start,
+        bytes_end,
+    );
+    show_scope_details(info, options, file, path, None, scope, result);
+    if scope.inspector_collapsed || !shows_children_at(options, 0) {
+        return 1;
+    }
+    let scope_path = NodePath {
+        file: file.index,
+        layout: file.layout,
+        children: path.clone(),
+    };
+    let groups = name_groups(options, &scope_path, scope);
+    if groups.is_empty() {
+        return 1;
+    }
+    let shows_sections = shows_children_at(options, 1);
+    let rows = if shows_sections {
+        groups
+            .iter()
+            .flat_map(|group| &group.sections)
+            .filter_map(|section| scope.descendant(&section.path))
+            .map(|section| 2 + visible_depth(options, section, 2))
+            .max()
+            .unwrap_or(2)
+    } else {
+        2
+    };
+    if result == PaintResult::Culled {
+        return rows;
+    }
+
+    let group_y = top_y + options.rect_height + options.spacing;
+    let group_ranges = packed_ranges(
+        options,
+        groups.iter().map(|group| group.len),
+        bytes_start,
+        bytes_end,
+    );
+    for (group, (group_start, group_end)) in groups.iter().zip(group_ranges) {
+        let result = paint_name_group(
+            info,
+            options,
+            file,
+            scope,
+            group,
+            group_y,
+            group_start,
+            group_end,
+        );
+        if result == PaintResult::Culled || !shows_sections {
+            continue;
+        }
+        let section_ranges = packed_ranges(
+            options,
+            group.sections.iter().map(|section| section.len),
+            group_start,
+            group_end,
+        );
+        for (grouped, (start, end)) in group.sections.iter().zip(section_ranges) {
+            let Some(section) = scope.descendant_mut(&grouped.path) else {
+                continue;
+            };
+            let scope_depth = path.len();
+            path.extend(&grouped.path);
+            paint_scope(
+                info,
+                options,
+                file,
+                path,
+                top_y,
+                2,
+                Some(grouped.parent_len),
+                section,
+                start,
+                end,
+            );
+            path.truncate(scope_depth);
+        }
+    }
+    rows
+}
+
+/// Draws the block of a [`NameGroup`] of `scope` at `bytes_start..bytes_end`, see [`paint_grouped`].
+/// Clicking it zooms to its sections.
+#[allow(clippy::too_many_arguments)]
+fn paint_name_group(
+    info: &Info,
+    options: &mut Options,
+    file: &ScopeFile,
+    scope: &FileNode,
+    group: &NameGroup,
+    top_y: f32,
+    bytes_start: u64,
+    bytes_end: u64,
+) -> PaintResult {
+    let start_x = info.point_from_bytes(options, bytes_start);
+    let stop_x = info.point_from_bytes(options, bytes_end);
+    if info.canvas.max.x < start_x
+        || stop_x < info.canvas.min.x
+        || stop_x - start_x < options.cull_width
+    {
+        return PaintResult::Culled;
+    }
+    let rect = Rect::from_min_max(
+        pos2(start_x, top_y),
+        pos2(stop_x, top_y + options.rect_height),
+    );
+    info.mark_painted(rect);
+    let is_hovered = info
+        .response
+        .hover_pos()
+        .is_some_and(|pos| rect.contains(pos));
+    if is_hovered && info.response.clicked() {
+        options.zoom_to_relative_bytes_range = Some((
+            info.ctx.input(|i| i.time),
+            (bytes_start - info.start_bytes, bytes_end - info.start_bytes),
+        ));
+    }
+
+    let color = if is_hovered {
+        HOVER_COLOR
+    } else {
+        let color = match options.color_by {
+            ColorBy::Size => {
+                color_from_size(info.size_range.fraction(group.len, options.size_scale))
+            }
+            ColorBy::Type => color_from_type(&group.ty, &group.name),
+            // The entropy of the sections weighted by their lengths.
+            ColorBy::Entropy => {
+                let total: f32 = group
+                    .sections
+                    .iter()
+                    .filter_map(|section| scope.descendant(&section.path))
+                    .map(|section| file.section_entropy(section) * section.len() as f32)
+                    .sum();
+                color_from_entropy(total / group.len as f32)
+            }
+        };
+        tint_scope_color(options, file, color)
+    };
+    let search_misses =
+        !info.search.is_empty() && !group.name.to_lowercase().contains(&info.search);
+    let alpha = if !options
+        .type_filter
+        .contains(&group.ty.category(&group.name))
+        || search_misses
+    {
+        FILTERED_ALPHA
+    } else {
+        1.0
+    };
+    let color = color * alpha;
+    info.painter.rect_filled(rect, options.rounding, color);
+
+    let numbers = Numbers::of(options);
+    let count = group.sections.len();
+    if stop_x - start_x > 32.0 {
+        let text = if options.label_sizes {
+            format!("{} ×{count} ({})", group.name, numbers.size(group.len))
+        } else {
+            format!("{} ×{count}", group.name)
+        };
+        info.mark_accessible(
+            Id::new(("name group", file.index, file.layout, &group.name)),
+            rect,
+            WidgetInfo::labeled(
+                WidgetType::Label,
+                format!(
+                    "{}, {count} sections, {}",
+                    group.name,
+                    numbers.size(group.len)
+                ),
+            ),
+        );
+        let text_color = text_color_on(color, info.canvas_color.into()).gamma_multiply(alpha);
+        let pos = pos2(
+            start_x + 4.0,
+            top_y + 0.5 * (options.rect_height - info.text_height),
+        );
+        let painter = info.painter.with_clip_rect(rect.intersect(info.canvas));
+        let pos = painter.round_pos_to_pixels(pos);
+        painter.text(
+            pos,
+            Align2::LEFT_TOP,
+            text,
+            info.font_id.clone(),
+            text_color,
+        );
+    }
+
+    if is_hovered {
+        egui::show_tooltip_at_pointer(&info.ctx, Id::new("inspector_tooltip"), |ui| {
+            egui::Grid::new("name_group_tooltip")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.monospace("name");
+                    ui.monospace(&group.name);
+                    ui.end_row();
+
+                    ui.monospace("sections");
+                    ui.monospace(count.to_string());
+                    ui.end_row();
+
+                    ui.monospace("len");
+                    ui.monospace(format!(
+                        "{} ({})",
+                        numbers.offset(group.len),
+                        numbers.size(group.len)
+                    ));
+                    ui.end_row();
+
+                    ui.monospace("share");
+                    ui.monospace(format!(
+                        "{:.1}%",
+                        100.0 * group.len as f64 / scope.len().max(1) as f64
+                    ));
+                    ui.end_row();
+                });
+        });
+        PaintResult::Hovered
+    } else {
+        PaintResult::Normal
+    }
+}
+
+/// True if the scope at `path` should be dimmed, because of [`Options::type_filter`] or [`Options::search`].
+/// `search` is the search in lowercase.
+fn is_filtered_out(options: &Options, search: &str, path: &[usize], section: &FileNode) -> bool {
+    // The root is the whole file so it is never dimmed.
+    !path.is_empty()
+        && (!options.type_filter.contains(&section.category())
+            || !search.is_empty() && !section.name.to_lowercase().contains(search))
+}
+
+/// The fill of a scope that isn't hovered, according to [`Options::color_by`] or `diff` when comparing against the baseline.
+fn scope_color(
+    options: &Options,
+    size_range: &SizeRange,
+    file: &ScopeFile,
+    section: &FileNode,
+    diff: Option<NodeDiff>,
+) -> Rgba {
+    if let Some(diff) = diff {
+        return color_from_diff(diff);
+    }
+    if matches!(section.ty, SectionType::Gap) {
+        return GAP_COLOR;
+    }
+    let color = match options.color_by {
+        ColorBy::Size => color_from_size(size_range.fraction(section.len(), options.size_scale)),
+        ColorBy::Type => color_from_type(&section.ty, &section.name),
+        ColorBy::Entropy => color_from_entropy(file.section_entropy(section)),
+    };
+    tint_scope_color(options, file, color)
+}
+
+/// Tints `color` for [`Options::tint_files`].
+fn tint_scope_color(options: &Options, file: &ScopeFile, color: Rgba) -> Rgba {
+    // Tinting would shift the colors away from the entropy they stand for.
+    if options.tint_files && options.color_by != ColorBy::Entropy {
+        tint_for_file(color, file.index)
+    } else {
+        color
+    }
+}
+
+/// True if `section` or any of its descendants is in [`Options::type_filter`].
+fn passes_type_filter(options: &Options, section: &FileNode) -> bool {
+    options.type_filter.contains(&section.category())
+        || section
+            .children
+            .iter()
+            .any(|child| passes_type_filter(options, child))
+}
+
+fn paint_section_details(
+    ui: &mut Ui,
+    numbers: Numbers,
+    file: &ScopeFile,
+    parent_len: Option<u64>,
+    diff: Option<NodeDiff>,
+    section: &FileNode,
+) {
     egui::Grid::new("section_details_tooltip")
         .num_columns(2)
         .show(ui, |ui| {
@@ -595,17 +3535,77 @@ fn paint_section_details(ui: &mut Ui, section: &FileNode) {
             ui.end_row();
 
             ui.monospace("file start");
-            ui.monospace(format!("0x{:x}", section.file_bytes_start));
+            ui.monospace(numbers.offset(section.file_bytes_start));
             ui.end_row();
 
             ui.monospace("ram start");
-            ui.monospace(format!("0x{:x}", section.ram_bytes_start));
+            ui.monospace(numbers.offset(section.ram_bytes_start));
             ui.end_row();
 
             ui.monospace("len");
-            ui.monospace(format!("0x{:x}", section.len()));
+            ui.monospace(format!(
+                "{} ({})",
+                numbers.offset(section.len()),
+                numbers.size(section.len())
+            ));
             ui.end_row();
 
+            ui.monospace("% of file");
+            ui.monospace(percent(section.len(), file.len));
+            ui.end_row();
+
+            if let Some(entry_point) = file.entry_point_in(section) {
+                ui.monospace("entry point");
+                ui.monospace(format!(
+                    "{}, execution starts here",
+                    numbers.offset(entry_point)
+                ));
+                ui.end_row();
+            }
+
+            if let Some(parent_len) = parent_len {
+                ui.monospace("% of parent");
+                ui.monospace(percent(section.len(), parent_len));
+                ui.end_row();
+            }
+
+            if let Some(diff) = diff {
+                ui.monospace("vs baseline");
+                ui.monospace(match diff {
+                    NodeDiff::Added => "added".to_owned(),
+                    NodeDiff::Identical => "identical".to_owned(),
+                    NodeDiff::Changed { delta } => format!("changed, {}", numbers.delta(delta)),
+                });
+                ui.end_row();
+            }
+
+            if file.entropy.is_some() {
+                ui.monospace("entropy");
+                ui.monospace(format!(
+                    "{:.2} bits per byte",
+                    file.section_entropy(section)
+                ));
+                ui.end_row();
+            }
+
+            if matches!(section.ty, SectionType::Gap) {
+                let bytes = section.file_data(file.data).unwrap_or_default();
+                ui.monospace("contents");
+                ui.monospace(if bytes.iter().all(|byte| *byte == 0) {
+                    "zero padding"
+                } else {
+                    "unknown, not all zero"
+                });
+                ui.end_row();
+            }
+
+            // Zero initialized scopes have no contents in the file to hash.
+            if section.children.is_empty() && section.category() != SectionTypeCategory::Bss {
+                ui.monospace("crc32");
+                ui.monospace(format!("{:08x}", section.content_hash(file.data)));
+                ui.end_row();
+            }
+
             for (name, value) in &section.notes {
                 ui.monospace(name);
                 ui.monospace(value);
@@ -614,16 +3614,140 @@ fn paint_section_details(ui: &mut Ui, section: &FileNode) {
         });
 }
 
-fn paint_binary_info(info: &Info, file: &mut ExecutableFile, pos: Pos2) {
+/// The menu shown when right-clicking [`Options::context_menu_scope`].
+fn scope_context_menu(ui: &mut Ui, options: &Options, files: &[ExecutableFile]) {
+    let Some((path, section)) = options
+        .context_menu_scope
+        .as_ref()
+        .and_then(|path| Some((path, path.resolve(files)?)))
+    else {
+        ui.close_menu();
+        return;
+    };
+    if ui.button("Copy details").clicked() {
+        let root = files[path.file].root(path.layout);
+        let parent_len = path
+            .children
+            .split_last()
+            .and_then(|(_, parent)| root.descendant(parent))
+            .map(|parent| parent.len());
+        let details = section_details_text(Numbers::of(options), root.len(), parent_len, section);
+        ui.output_mut(|o| o.copied_text = details);
+        ui.close_menu();
+    }
+    if ui.button("Copy name").clicked() {
+        ui.output_mut(|o| o.copied_text = section.name.clone());
+        ui.close_menu();
+    }
+}
+
+/// The main rows of [`paint_section_details`] as plain text, for pasting into notes or a bug report.
+/// `file_len` and `parent_len` are the lengths of the root and the parent of `section`, as in the tooltip.
+fn section_details_text(
+    numbers: Numbers,
+    file_len: u64,
+    parent_len: Option<u64>,
+    section: &FileNode,
+) -> String {
+    let mut text = format!(
+        "{}\nfile range: {} - {}\nram range: {} - {}\nsize: {} ({})\n% of file: {}\n",
+        section.name,
+        numbers.offset(section.file_bytes_start),
+        numbers.offset(section.file_bytes_end),
+        numbers.offset(section.ram_bytes_start),
+        numbers.offset(section.ram_bytes_end),
+        numbers.offset(section.len()),
+        numbers.size(section.len()),
+        percent(section.len(), file_len),
+    );
+    if let Some(parent_len) = parent_len {
+        text.push_str(&format!(
+            "% of parent: {}\n",
+            percent(section.len(), parent_len)
+        ));
+    }
+    text.push_str(&format!("type: {:?}\n", section.category()));
+    for (name, value) in &section.notes {
+        text.push_str(&format!("{name}: {value}\n"));
+    }
+    text
+}
+
+fn percent(len: u64, total: u64) -> String {
+    if total == 0 {
+        "-".to_owned()
+    } else {
+        format!("{:.2}%", len as f64 / total as f64 * 100.0)
+    }
+}
+
+/// A button showing `symbol` at `pos` beside the header of `file`, `action` describes what it does.
+/// Returns where it was painted and whether it was clicked.
+fn paint_header_button(
+    info: &Info,
+    symbol: &str,
+    action: &str,
+    file: &ExecutableFile,
+    pos: Pos2,
+) -> (Rect, bool) {
+    let galley = info.ctx.fonts(|f| {
+        f.layout_no_wrap(
+            symbol.to_owned(),
+            info.font_id.clone(),
+            Color32::PLACEHOLDER,
+        )
+    });
+    let rect = Rect::from_min_size(pos, galley.size());
+    let is_hovered = info
+        .response
+        .hover_pos()
+        .is_some_and(|mouse_pos| rect.expand(2.0).contains(mouse_pos));
+
+    let text_color = if is_hovered {
+        info.text_color
+    } else {
+        info.text_color.gamma_multiply(0.5)
+    };
+    info.mark_painted(rect.expand(2.0));
+    info.painter
+        .rect_filled(rect.expand(2.0), 0.0, info.canvas_color);
+    info.painter.galley(rect.min, galley, text_color);
+    let label = format!("{action} {}", file.name);
+    info.mark_accessible(
+        Id::new(("file_button", action, file.data.as_ptr())),
+        rect,
+        WidgetInfo::labeled(WidgetType::Button, &label),
+    );
+    if is_hovered {
+        egui::show_tooltip_at_pointer(&info.ctx, Id::new("file_button_tooltip"), |ui| {
+            ui.label(label);
+        });
+    }
+    (rect, is_hovered && info.response.clicked())
+}
+
+/// Returns where the header was painted.
+fn paint_binary_info(
+    info: &Info,
+    numbers: Numbers,
+    index: usize,
+    file: &mut ExecutableFile,
+    pos: Pos2,
+) -> Rect {
     let collapsed_symbol = if file.inspector_collapsed {
         "⏵"
     } else {
         "⏷"
     };
 
+    let stripped = match file.is_stripped() {
+        Some(true) => " [stripped]",
+        Some(false) => " [not stripped]",
+        None => "",
+    };
     let galley = info.ctx.fonts(|f| {
         f.layout_no_wrap(
-            format!("{} {}", collapsed_symbol, file.name.clone()),
+            format!("{} {}{stripped}", collapsed_symbol, file.name.clone()),
             info.font_id.clone(),
             egui::Color32::PLACEHOLDER,
         )
@@ -638,20 +3762,66 @@ fn paint_binary_info(info: &Info, file: &mut ExecutableFile, pos: Pos2) {
     };
 
     let text_color = if is_hovered {
-        Color32::WHITE
+        info.text_color
     } else {
-        Color32::from_white_alpha(229)
+        info.text_color.gamma_multiply(0.9)
     };
     let back_color = if is_hovered {
-        Color32::from_black_alpha(100)
+        info.text_color.gamma_multiply(0.2)
     } else {
-        Color32::BLACK
+        info.canvas_color
     };
 
+    info.mark_painted(rect.expand(2.0));
     info.painter.rect_filled(rect.expand(2.0), 0.0, back_color);
     info.painter.galley(rect.min, galley, text_color);
+    info.mark_accessible(
+        Id::new(("file", index)),
+        rect,
+        WidgetInfo::labeled(
+            WidgetType::CollapsingHeader,
+            format!(
+                "{}{stripped}, {}",
+                file.name,
+                numbers.size(file.data.len() as u64)
+            ),
+        ),
+    );
 
     if is_hovered && info.response.clicked() {
         file.inspector_collapsed = !file.inspector_collapsed;
     }
+    rect
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sections::test_elf::TestElf;
+    use goblin::elf::section_header::{SHF_ALLOC, SHF_EXECINSTR, SHT_PROGBITS};
+
+    #[test]
+    fn initial_zoom_uses_the_shown_layout() {
+        let mut elf = TestElf::new_64();
+        elf.section(".text", SHT_PROGBITS, vec![0x90; 0x20]);
+        elf.last().sh_flags = (SHF_ALLOC | SHF_EXECINSTR) as u64;
+        elf.last().sh_addr = 0x8000;
+        let file = ExecutableFile::load_from_bytes("test".into(), &elf.build()).unwrap();
+        let zoom_to = |layout_view| {
+            let mut options = Options {
+                initial_zoom_section: ".text".into(),
+                layout_view,
+                ..Options::default()
+            };
+            options.zoom_to_initial_section(&file, 0.0);
+            options.zoom_to_relative_bytes_range.unwrap().1
+        };
+
+        let text = file.file_root.find_section(".text").unwrap();
+        assert_eq!(
+            zoom_to(LayoutView::File),
+            (text.bytes_start, text.bytes_end)
+        );
+        assert_eq!(zoom_to(LayoutView::Ram), (0x8000, 0x8020));
+    }
 }
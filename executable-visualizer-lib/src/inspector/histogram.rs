@@ -0,0 +1,176 @@
+//! A panel showing how the sizes of the sections are distributed, see [`Options::show_histogram`].
+//!
+//! The timeline shows where sections are, this shows how many sections there are of each size,
+//! so a few unusually large sections or a mass of tiny ones stand out.
+
+use super::{for_each_leaf, FilesKey, LayoutView, NodePath, Numbers, Options};
+use crate::sections::{ExecutableFile, Layout};
+use egui::*;
+
+/// Height of the tallest bar of the histogram.
+const HISTOGRAM_HEIGHT: f32 = 100.0;
+/// How many of the largest sections are listed below the histogram.
+const LARGEST: usize = 20;
+
+/// A section without children of its own, the sizes that are counted.
+/// Sections whose children are the entries of a table count as a whole, see [`crate::sections::FileNode::has_entries`].
+#[derive(Clone, Debug)]
+struct Leaf {
+    path: NodePath,
+    len: u64,
+}
+
+/// The leaves of the files in a layout, largest first.
+/// Finding them walks every node, so they are kept until the files or the layout change.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Leaves {
+    key: Option<(FilesKey, Layout)>,
+    leaves: Vec<Leaf>,
+}
+
+impl Leaves {
+    fn update(&mut self, files: &[ExecutableFile], layout: Layout) {
+        let key = Some((FilesKey::of(files), layout));
+        if self.key == key {
+            return;
+        }
+        self.key = key;
+        self.leaves.clear();
+        for (index, file) in files.iter().enumerate() {
+            for_each_leaf(file.root(layout), &mut vec![], &mut |path, _, leaf| {
+                self.leaves.push(Leaf {
+                    path: NodePath {
+                        file: index,
+                        layout,
+                        children: path.to_vec(),
+                    },
+                    len: leaf.len(),
+                })
+            });
+        }
+        self.leaves.sort_by_key(|leaf| std::cmp::Reverse(leaf.len));
+    }
+}
+
+/// Draws a histogram of the sizes of the sections without children in `files`, in buckets that double in size,
+/// and a bar for each of the largest sections. Clicking a bar selects and zooms to its section.
+pub(super) fn ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    ui.heading("Section sizes");
+
+    // The RAM layout when it is the only one drawn, otherwise the layout of the file on disk.
+    let layout = match options.layout_view {
+        LayoutView::Ram => Layout::Ram,
+        LayoutView::Both | LayoutView::File => Layout::File,
+    };
+    options.histogram_leaves.update(files, layout);
+    // Taken while drawing so `options` can be changed by clicks, put back at the end.
+    let cache = std::mem::take(&mut options.histogram_leaves);
+    let leaves = &cache.leaves;
+    if leaves.is_empty() {
+        ui.label("No sections to show.");
+        options.histogram_leaves = cache;
+        return;
+    }
+
+    let numbers = Numbers::of(options);
+    let mut clicked = None;
+
+    // Bucket `i` holds the sizes from 2^i up to 2^(i + 1), from the smallest leaf to the largest.
+    let bucket_of = |len: u64| len.ilog2() as usize;
+    let first_bucket = bucket_of(leaves.last().unwrap().len);
+    let last_bucket = bucket_of(leaves[0].len);
+    let mut buckets = vec![vec![]; last_bucket - first_bucket + 1];
+    for leaf in leaves {
+        buckets[bucket_of(leaf.len) - first_bucket].push(leaf);
+    }
+    let max_count = buckets.iter().map(Vec::len).max().unwrap_or(1);
+
+    let (rect, _) =
+        ui.allocate_exact_size(vec2(ui.available_width(), HISTOGRAM_HEIGHT), Sense::hover());
+    let bar_width = rect.width() / buckets.len() as f32;
+    for (i, bucket) in buckets.iter().enumerate() {
+        let height = HISTOGRAM_HEIGHT * bucket.len() as f32 / max_count as f32;
+        let column = Rect::from_x_y_ranges(
+            rect.left() + i as f32 * bar_width..=rect.left() + (i + 1) as f32 * bar_width,
+            rect.y_range(),
+        );
+        let bar = Rect::from_x_y_ranges(
+            column.x_range().shrink(1.0),
+            rect.bottom() - height..=rect.bottom(),
+        );
+        let response = ui.interact(column, ui.id().with(("size_bucket", i)), Sense::click());
+        let color = if response.hovered() {
+            ui.visuals().strong_text_color()
+        } else {
+            ui.visuals().selection.bg_fill
+        };
+        ui.painter().rect_filled(bar, 0.0, color);
+
+        let bucket_start = 1u64 << (first_bucket + i);
+        let response = response.on_hover_text(format!(
+            "{} sections from {} to {}\nClick to zoom to the largest of them",
+            bucket.len(),
+            numbers.size(bucket_start),
+            numbers.size(bucket_start * 2),
+        ));
+        if response.clicked() {
+            clicked = bucket.first().map(|leaf| &leaf.path);
+        }
+    }
+    ui.horizontal(|ui| {
+        ui.small(numbers.size(1 << first_bucket));
+        ui.with_layout(egui::Layout::right_to_left(Align::Center), |ui| {
+            ui.small(numbers.size(1 << (last_bucket + 1)));
+        });
+    });
+
+    ui.separator();
+    ui.strong(format!("Largest {} sections", LARGEST.min(leaves.len())));
+    let largest_len = leaves[0].len;
+    let row_height = ui.text_style_height(&TextStyle::Body);
+    ScrollArea::vertical()
+        .id_source("largest_sections")
+        .show(ui, |ui| {
+            for leaf in leaves.iter().take(LARGEST) {
+                let Some(node) = leaf.path.resolve(files) else {
+                    continue;
+                };
+                let (rect, response) =
+                    ui.allocate_exact_size(vec2(ui.available_width(), row_height), Sense::click());
+                let fraction = leaf.len as f32 / largest_len as f32;
+                let bar = Rect::from_min_size(rect.min, vec2(rect.width() * fraction, row_height));
+                let fill = if response.hovered() {
+                    ui.visuals().widgets.hovered.weak_bg_fill
+                } else {
+                    ui.visuals().widgets.inactive.weak_bg_fill
+                };
+                ui.painter().rect_filled(bar, 2.0, fill);
+                let text = format!("{} ({})", node.name, numbers.size(leaf.len));
+                ui.painter().with_clip_rect(rect).text(
+                    rect.left_center() + vec2(4.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    text,
+                    TextStyle::Body.resolve(ui.style()),
+                    ui.visuals().text_color(),
+                );
+                if response
+                    .on_hover_text(format!(
+                        "{}\nClick to zoom to it",
+                        files[leaf.path.file].name
+                    ))
+                    .clicked()
+                {
+                    clicked = Some(&leaf.path);
+                }
+            }
+        });
+
+    if let Some(path) = clicked {
+        if let Some(node) = path.resolve(files) {
+            options.zoom_to_relative_bytes_range =
+                Some((ui.input(|i| i.time), (node.bytes_start, node.bytes_end)));
+            options.selected = Some(path.clone());
+        }
+    }
+    options.histogram_leaves = cache;
+}
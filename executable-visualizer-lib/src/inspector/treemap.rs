@@ -0,0 +1,360 @@
+//! Drawing files as treemaps, see [`super::Visualization::Treemap`].
+//!
+//! Each scope is a tile with an area proportional to its size, its children are tiled within it.
+//! Unlike the timeline, tiny sections are enlarged to [`MIN_TILE_AREA`] when their parent has room,
+//! so they can still be hovered and clicked, at the expense of their larger siblings.
+
+use super::{
+    is_filtered_out, paint_section_details, passes_type_filter, scope_color, scope_context_menu,
+    shows_children_at, text_color_on, ColorBy, NodePath, Numbers, Options, ScopeFile, SizeRange,
+    FILTERED_ALPHA, HOVER_COLOR,
+};
+use crate::sections::{ExecutableFile, FileNode, Layout};
+use egui::*;
+
+/// Space between the edge of a tile and the tiles of its children.
+const PADDING: f32 = 2.0;
+/// Tiles narrower or shorter than this are drawn without their children.
+const MIN_TILE_SIZE: f32 = 4.0;
+/// The area tiles of tiny sections are enlarged to, or an even share of the parent if that is smaller.
+const MIN_TILE_AREA: f32 = 8.0 * 8.0;
+/// Tiles narrower than this are drawn without their name.
+const MIN_LABEL_WIDTH: f32 = 40.0;
+
+/// What is shared by every tile painted this frame.
+struct Treemap {
+    painter: Painter,
+    response: Response,
+    font_id: FontId,
+    text_height: f32,
+    size_range: SizeRange,
+    /// [`Options::search`] in lowercase.
+    search: String,
+    /// What the canvas is filled with, behind translucent tiles.
+    canvas_color: Color32,
+}
+
+/// Fills the available space with a treemap of each shown layout of each file, or only of [`Options::focused`].
+/// Files are stacked vertically and the file and RAM layouts of a file are placed side by side.
+pub(super) fn ui(ui: &mut Ui, options: &mut Options, files: &[ExecutableFile]) {
+    let canvas = ui.available_rect_before_wrap();
+    let response = ui.allocate_rect(canvas, Sense::click());
+    // Set again by the tile under the pointer as it is painted.
+    if response.secondary_clicked() {
+        options.context_menu_scope = None;
+    }
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let treemap = Treemap {
+        painter: ui.painter_at(canvas),
+        response,
+        text_height: ui.fonts(|f| f.row_height(&font_id)),
+        font_id,
+        size_range: options.size_range(files),
+        search: options.search.to_lowercase(),
+        canvas_color: ui.visuals().extreme_bg_color,
+    };
+
+    // The scopes drawn as the root of a treemap, a row for each file.
+    let rows: Vec<Vec<NodePath>> = match &options.focused {
+        Some(focused) => vec![vec![focused.clone()]],
+        None => (0..files.len())
+            .map(|file| {
+                [Layout::File, Layout::Ram]
+                    .into_iter()
+                    .filter(|layout| options.layout_view.shows(*layout))
+                    .map(|layout| NodePath {
+                        file,
+                        layout,
+                        children: vec![],
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+    if rows.is_empty() {
+        return;
+    }
+
+    let row_height = canvas.height() / rows.len() as f32;
+    for (row, roots) in rows.into_iter().enumerate() {
+        let top = canvas.top() + row as f32 * row_height;
+        let column_width = canvas.width() / roots.len().max(1) as f32;
+        for (column, root_path) in roots.into_iter().enumerate() {
+            let (Some(file), Some(root)) = (files.get(root_path.file), root_path.resolve(files))
+            else {
+                continue;
+            };
+            let (index, layout, mut path) = (root_path.file, root_path.layout, root_path.children);
+            let left = canvas.left() + column as f32 * column_width;
+            let cell = Rect::from_min_size(pos2(left, top), vec2(column_width, row_height));
+
+            let title = match layout {
+                Layout::File => file.name.clone(),
+                Layout::Ram => format!("{} (RAM)", file.name),
+            };
+            treemap.painter.text(
+                cell.min + vec2(PADDING, 0.0),
+                Align2::LEFT_TOP,
+                title,
+                treemap.font_id.clone(),
+                ui.visuals().strong_text_color(),
+            );
+            let mut rect = cell.shrink(PADDING);
+            rect.min.y += treemap.text_height;
+            if rect.width() < MIN_TILE_SIZE || rect.height() < MIN_TILE_SIZE {
+                continue;
+            }
+
+            let entropy = (options.color_by == ColorBy::Entropy).then(|| file.entropy());
+            let scope_file = ScopeFile {
+                index,
+                layout,
+                data: &file.data,
+                len: file.root(layout).len(),
+                is_baseline: options
+                    .baseline
+                    .as_ref()
+                    .is_some_and(|baseline| baseline.is_file(file)),
+                entry_point: file.entry_point,
+                entropy: entropy.as_deref(),
+            };
+            paint_tile(&treemap, options, &scope_file, &mut path, None, root, rect);
+        }
+    }
+    treemap
+        .response
+        .context_menu(|ui| scope_context_menu(ui, options, files));
+}
+
+/// Paints `section` filling `rect` and its children within it.
+/// Returns true if the pointer is over this tile, so its ancestors don't also show a tooltip.
+fn paint_tile(
+    treemap: &Treemap,
+    options: &mut Options,
+    file: &ScopeFile,
+    path: &mut Vec<usize>,
+    parent_len: Option<u64>,
+    section: &FileNode,
+    rect: Rect,
+) -> bool {
+    let diff = if options.color_by_baseline {
+        options.compare_to_baseline(file, section)
+    } else {
+        None
+    };
+    let alpha = if is_filtered_out(options, &treemap.search, path, section) {
+        FILTERED_ALPHA
+    } else {
+        1.0
+    };
+    let color = scope_color(options, &treemap.size_range, file, section, diff) * alpha;
+    let text_color = text_color_on(color, treemap.canvas_color.into()).gamma_multiply(alpha);
+    treemap.painter.rect_filled(rect, 0.0, color);
+    treemap
+        .painter
+        .rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::from_black_alpha(120)));
+
+    let mut inner = rect.shrink(PADDING);
+    if rect.width() >= MIN_LABEL_WIDTH && rect.height() >= treemap.text_height + 2.0 * PADDING {
+        treemap.painter.with_clip_rect(rect).text(
+            rect.min + vec2(PADDING, PADDING),
+            Align2::LEFT_TOP,
+            &section.name,
+            treemap.font_id.clone(),
+            text_color,
+        );
+        inner.min.y += treemap.text_height;
+    }
+
+    // The tile of the focused scope or of the root is the first row.
+    let row = path.len()
+        - options
+            .focused
+            .as_ref()
+            .map_or(0, |focused| focused.children.len());
+    let mut child_hovered = false;
+    if inner.width() >= MIN_TILE_SIZE
+        && inner.height() >= MIN_TILE_SIZE
+        && !section.inspector_collapsed
+        && shows_children_at(options, row)
+    {
+        let mut children: Vec<(usize, u64)> = section
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| {
+                child.len() > 0 && (!options.hide_filtered || passes_type_filter(options, child))
+            })
+            .map(|(i, child)| (i, child.len()))
+            .collect();
+        children.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
+        for (i, child_rect) in squarify(&children, inner) {
+            path.push(i);
+            child_hovered |= paint_tile(
+                treemap,
+                options,
+                file,
+                path,
+                Some(section.len()),
+                &section.children[i],
+                child_rect,
+            );
+            path.pop();
+        }
+    }
+
+    let node_path = || NodePath {
+        file: file.index,
+        layout: file.layout,
+        children: path.clone(),
+    };
+    if options
+        .selected
+        .as_ref()
+        .is_some_and(|selected| selected.is(file, path))
+    {
+        treemap
+            .painter
+            .rect_stroke(rect, 0.0, Stroke::new(2.0, Color32::WHITE));
+    }
+
+    let hovered = treemap
+        .response
+        .hover_pos()
+        .is_some_and(|pos| rect.contains(pos));
+    if !hovered || child_hovered {
+        return hovered;
+    }
+    treemap
+        .painter
+        .rect_stroke(rect, 0.0, Stroke::new(2.0, HOVER_COLOR));
+    egui::show_tooltip_at_pointer(&treemap.response.ctx, Id::new("inspector_tooltip"), |ui| {
+        paint_section_details(ui, Numbers::of(options), file, parent_len, diff, section);
+    });
+    if treemap.response.secondary_clicked() {
+        options.context_menu_scope = Some(node_path());
+    }
+    if treemap.response.double_clicked() {
+        options.focused = Some(node_path());
+    } else if treemap.response.clicked() {
+        options.selected = Some(node_path());
+    }
+    if treemap.response.clicked() {
+        // Applied when switching back to the timeline.
+        let time = treemap.response.ctx.input(|i| i.time);
+        options.zoom_to_relative_bytes_range =
+            Some((time, (section.bytes_start, section.bytes_end)));
+    }
+    true
+}
+
+/// Splits `rect` into a tile for each of `sizes`, keeping the tiles close to square.
+/// `sizes` are pairs of an index and a size, sorted from largest to smallest, the returned tiles keep the indexes.
+/// Tiles are proportional to their sizes, except that none is smaller than [`MIN_TILE_AREA`].
+///
+/// This is the squarified treemap algorithm of Bruls, Huizing and van Wijk:
+/// tiles are laid out in rows along the shorter side of the remaining space, and a row is ended once adding another tile would make its tiles less square.
+fn squarify(sizes: &[(usize, u64)], rect: Rect) -> Vec<(usize, Rect)> {
+    let total: u64 = sizes.iter().map(|(_, size)| size).sum();
+    if total == 0 {
+        return vec![];
+    }
+    // Raising the smallest tiles to the minimum shrinks the others, which may push more of them below it.
+    let min_area = MIN_TILE_AREA.min(rect.area() / sizes.len() as f32);
+    let mut proportional = sizes.len();
+    let scale = loop {
+        let proportional_total: u64 = sizes[..proportional].iter().map(|(_, size)| size).sum();
+        let rest_area = (sizes.len() - proportional) as f32 * min_area;
+        let scale = (rect.area() - rest_area) / proportional_total.max(1) as f32;
+        match proportional.checked_sub(1) {
+            Some(last) if (sizes[last].1 as f32 * scale) < min_area => proportional = last,
+            _ => break scale,
+        }
+    };
+    let areas: Vec<f32> = sizes
+        .iter()
+        .enumerate()
+        .map(|(i, (_, size))| {
+            if i < proportional {
+                *size as f32 * scale
+            } else {
+                min_area
+            }
+        })
+        .collect();
+
+    // The aspect ratio of the least square tile in a row of `row` areas along a side of length `side`.
+    let worst = |row: &[f32], side: f32| {
+        let sum: f32 = row.iter().sum();
+        let max = row.iter().copied().fold(f32::MIN, f32::max);
+        let min = row.iter().copied().fold(f32::MAX, f32::min);
+        let side_squared = side * side;
+        let sum_squared = sum * sum;
+        (side_squared * max / sum_squared).max(sum_squared / (side_squared * min))
+    };
+
+    let mut tiles = Vec::with_capacity(sizes.len());
+    let mut rest = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = rest.width().min(rest.height());
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst(&areas[start..=end], side) <= worst(&areas[start..end], side)
+        {
+            end += 1;
+        }
+
+        let row_area: f32 = areas[start..end].iter().sum();
+        if rest.width() >= rest.height() {
+            // The row is a column along the left edge.
+            let width = row_area / rest.height();
+            let mut y = rest.min.y;
+            for (i, area) in (start..end).zip(&areas[start..end]) {
+                let height = area / width;
+                tiles.push((
+                    sizes[i].0,
+                    Rect::from_min_size(pos2(rest.min.x, y), vec2(width, height)),
+                ));
+                y += height;
+            }
+            rest.min.x += width;
+        } else {
+            // The row runs along the top edge.
+            let height = row_area / rest.width();
+            let mut x = rest.min.x;
+            for (i, area) in (start..end).zip(&areas[start..end]) {
+                let width = area / height;
+                tiles.push((
+                    sizes[i].0,
+                    Rect::from_min_size(pos2(x, rest.min.y), vec2(width, height)),
+                ));
+                x += width;
+            }
+            rest.min.y += height;
+        }
+        start = end;
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_tiles_are_enlarged() {
+        let rect = Rect::from_min_size(Pos2::ZERO, vec2(100.0, 100.0));
+        let tiles = squarify(&[(0, 1_000_000), (1, 10), (2, 1)], rect);
+        let area = |index| tiles.iter().find(|(i, _)| *i == index).unwrap().1.area();
+        assert!((area(1) - MIN_TILE_AREA).abs() < 0.01);
+        assert!((area(2) - MIN_TILE_AREA).abs() < 0.01);
+        assert!((area(0) + 2.0 * MIN_TILE_AREA - rect.area()).abs() < 0.1);
+
+        // Without room for the minimum every tile gets an even share.
+        let rect = Rect::from_min_size(Pos2::ZERO, vec2(10.0, 10.0));
+        for (_, tile) in squarify(&[(0, 1_000_000), (1, 10), (2, 1), (3, 1)], rect) {
+            assert!((tile.area() - 25.0).abs() < 0.01);
+        }
+    }
+}
@@ -1,6 +1,30 @@
+//! Visualize whats taking up space in an executable.
+//!
+//! The intended entry points are:
+//! * [`sections`] - loading executables into a tree of [`FileNode`]s via [`ExecutableFile`].
+//! * [`diff`] - comparing files against a [`diff::Baseline`].
+//! * [`inspector`] - the egui widget [`inspector::ui`] that draws loaded files, configured by [`Options`].
+//! * [`report`] - Markdown tables of section sizes.
+//! * [`entropy`] - how random the bytes of a file look, see [`ExecutableFile::entropy`].
+//! * `dwarf` - the size of the code generated from each source file, only with the `dwarf` feature.
+//! * [`app`] - [`ExampleApp`], a ready to use [`eframe::App`] wrapping the inspector.
+//!
+//! Anything not reachable from these modules is an implementation detail.
+
 #![forbid(unsafe_code)]
 
 pub mod app;
+pub mod diff;
+#[cfg(feature = "dwarf")]
+pub mod dwarf;
+pub mod entropy;
 mod file_loader;
 pub mod inspector;
+pub mod report;
 pub mod sections;
+
+pub use app::ExampleApp;
+pub use inspector::Options;
+pub use sections::{
+    ExecutableFile, FileNode, Layout, NodeIter, SectionLink, SectionType, SectionTypeCategory,
+};
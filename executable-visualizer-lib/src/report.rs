@@ -0,0 +1,257 @@
+//! Text reports of what is taking up space in an [`ExecutableFile`], e.g. for pasting into a pull request.
+
+use crate::{
+    diff::{Baseline, NodeDiff},
+    sections::{ExecutableFile, FileNode, Layout, SectionType, SectionTypeCategory},
+};
+use std::fmt::Write;
+
+/// A GitHub flavored Markdown table of the top level sections of `file`, largest first.
+/// The headers are summed into a single row, and so are the gaps between sections.
+///
+/// When `baseline` is set, and isn't `file` itself, a column with the change of each section compared to the baseline is added.
+pub fn markdown(file: &ExecutableFile, baseline: Option<&Baseline>) -> String {
+    let root = &file.file_root;
+    let baseline = baseline.filter(|baseline| !baseline.is_file(file));
+    let Rows {
+        sections,
+        headers_len,
+        gaps_len,
+    } = Rows::of(root);
+
+    let mut out = String::new();
+    writeln!(out, "### {} ({} bytes)", file.name, root.len()).unwrap();
+    writeln!(out).unwrap();
+    match baseline {
+        Some(baseline) => {
+            writeln!(
+                out,
+                "| Section | Size | % of file | vs {} |",
+                baseline.name()
+            )
+            .unwrap();
+            writeln!(out, "|---|--:|--:|--:|").unwrap();
+        }
+        None => {
+            writeln!(out, "| Section | Size | % of file |").unwrap();
+            writeln!(out, "|---|--:|--:|").unwrap();
+        }
+    }
+    let mut row = |name: &str, len: u64, diff: Option<String>| {
+        let percent = if root.len() == 0 {
+            0.0
+        } else {
+            len as f64 / root.len() as f64 * 100.0
+        };
+        write!(out, "| {} | {len} | {percent:.2}% |", escape(name)).unwrap();
+        if let Some(diff) = diff {
+            write!(out, " {diff} |").unwrap();
+        }
+        writeln!(out).unwrap();
+    };
+    for section in sections {
+        let diff =
+            baseline.map(
+                |baseline| match baseline.compare(Layout::File, section, &file.data) {
+                    NodeDiff::Added => "added".to_owned(),
+                    NodeDiff::Identical => "identical".to_owned(),
+                    NodeDiff::Changed { delta: 0 } => "changed".to_owned(),
+                    NodeDiff::Changed { delta } => format!("{delta:+}"),
+                },
+            );
+        row(&section.name, section.len(), diff);
+    }
+    if headers_len > 0 {
+        // The headers are spread over many nodes so they are not compared against the baseline.
+        row("Headers", headers_len, baseline.map(|_| String::new()));
+    }
+    if gaps_len > 0 {
+        row("Gaps", gaps_len, baseline.map(|_| String::new()));
+    }
+    out
+}
+
+/// A JSON array with an object for each of `files`, listing the same rows as [`markdown`] without a baseline.
+/// For example `[{"name": "ls", "size": 142312, "sections": [{"name": ".text", "size": 76473, "percent": 53.74}]}]`.
+pub fn json(files: &[ExecutableFile]) -> String {
+    let mut out = String::new();
+    writeln!(out, "[").unwrap();
+    for (i, file) in files.iter().enumerate() {
+        let root = &file.file_root;
+        let Rows {
+            sections,
+            headers_len,
+            gaps_len,
+        } = Rows::of(root);
+        let mut rows: Vec<(&str, u64)> = sections
+            .iter()
+            .map(|section| (section.name.as_str(), section.len()))
+            .collect();
+        if headers_len > 0 {
+            rows.push(("Headers", headers_len));
+        }
+        if gaps_len > 0 {
+            rows.push(("Gaps", gaps_len));
+        }
+
+        writeln!(out, "  {{").unwrap();
+        writeln!(out, "    \"name\": {},", json_string(&file.name)).unwrap();
+        writeln!(out, "    \"size\": {},", root.len()).unwrap();
+        writeln!(out, "    \"sections\": [").unwrap();
+        for (j, (name, len)) in rows.iter().enumerate() {
+            let percent = if root.len() == 0 {
+                0.0
+            } else {
+                *len as f64 / root.len() as f64 * 100.0
+            };
+            let comma = if j + 1 < rows.len() { "," } else { "" };
+            writeln!(
+                out,
+                "      {{\"name\": {}, \"size\": {len}, \"percent\": {percent:.2}}}{comma}",
+                json_string(name)
+            )
+            .unwrap();
+        }
+        writeln!(out, "    ]").unwrap();
+        let comma = if i + 1 < files.len() { "," } else { "" };
+        writeln!(out, "  }}{comma}").unwrap();
+    }
+    writeln!(out, "]").unwrap();
+    out
+}
+
+/// A CSV table with a row for each section of each of `files`, the same sections as [`markdown`] and [`json`] list,
+/// for tracking sizes in a spreadsheet.
+/// The rows of each file are ordered by their start in the file, so exports of similar files diff cleanly.
+pub fn csv(files: &[ExecutableFile]) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "file,name,file_start,file_end,file_size,ram_start,ram_size,type"
+    )
+    .unwrap();
+    for file in files {
+        let mut sections = Rows::of(&file.file_root).sections;
+        sections.sort_by_key(|section| section.file_bytes_start);
+        for section in sections {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{:?}",
+                csv_field(&file.name),
+                csv_field(&section.name),
+                section.file_bytes_start,
+                section.file_bytes_end,
+                section.file_bytes_end - section.file_bytes_start,
+                section.ram_bytes_start,
+                section.ram_bytes_end - section.ram_bytes_start,
+                section.category(),
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// The rows of a report: the sections of a file largest first, with the headers and gaps summed separately.
+struct Rows<'a> {
+    sections: Vec<&'a FileNode>,
+    headers_len: u64,
+    gaps_len: u64,
+}
+
+impl<'a> Rows<'a> {
+    /// The sections directly under `root`, and those within the ELF segments under it,
+    /// since the segments only group the sections they load.
+    fn of(root: &'a FileNode) -> Self {
+        let mut rows = Rows {
+            sections: vec![],
+            headers_len: 0,
+            gaps_len: 0,
+        };
+        rows.add_children(root);
+        rows.sections.sort_by(|a, b| FileNode::by_size(b, a));
+        rows
+    }
+
+    fn add_children(&mut self, node: &'a FileNode) {
+        for child in &node.children {
+            if child.category() == SectionTypeCategory::Headers {
+                self.headers_len += child.len();
+            } else if matches!(child.ty, SectionType::Gap) {
+                self.gaps_len += child.len();
+            } else if matches!(child.ty, SectionType::ElfSegment { .. }) {
+                self.add_children(child);
+            } else {
+                self.sections.push(child);
+            }
+        }
+    }
+}
+
+/// `text` as a quoted JSON string.
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quote `text` if it contains characters that would break out of a CSV field.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Escape characters that would break out of a Markdown table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The test binary itself is an ELF file with its sections grouped into `PT_LOAD` segments.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn markdown_lists_sections_within_segments() {
+        let file = ExecutableFile::load_self();
+        let report = markdown(&file, None);
+        assert!(report.contains("| .text |"), "{report}");
+        assert!(report.contains("| .rodata |"), "{report}");
+        assert!(!report.contains("LOAD segment"), "{report}");
+
+        let headers_len: u64 = file
+            .file_root
+            .iter()
+            .filter(|node| node.category() == SectionTypeCategory::Headers)
+            .map(FileNode::len)
+            .sum();
+        assert_eq!(Rows::of(&file.file_root).headers_len, headers_len);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn json_and_csv_list_sections_within_segments() {
+        let files = [ExecutableFile::load_self()];
+        let json = json(&files);
+        assert!(json.contains("{\"name\": \".text\""), "{json}");
+        assert!(!json.contains("LOAD segment"), "{json}");
+
+        let csv = csv(&files);
+        assert!(csv
+            .lines()
+            .any(|line| line.split(',').nth(1) == Some(".text")));
+        assert!(!csv.contains("LOAD segment"), "{csv}");
+    }
+}
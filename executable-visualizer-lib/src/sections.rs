@@ -1,36 +1,319 @@
-use anyhow::{anyhow, Result};
+//! Loading executables into a tree of [`FileNode`]s.
+//!
+//! Start with [`ExecutableFile::load`] or [`ExecutableFile::load_from_bytes`].
+//! ELF files are loaded here, other formats in the submodules.
+
+mod archive;
+mod macho;
+mod pe;
+#[cfg(test)]
+pub(crate) mod test_elf;
+mod wasm;
+
+use crate::entropy::EntropyProfile;
+use anyhow::{anyhow, Context, Error, Result};
 use goblin::{
-    elf::section_header::{
-        shf_to_str, sht_to_str, SHF_ALLOC, SHF_FLAGS, SHT_DYNAMIC, SHT_NOBITS, SHT_NULL, SHT_REL,
-        SHT_RELA,
+    container::Ctx,
+    elf::program_header::{ProgramHeader, PF_R, PF_W, PF_X, PT_INTERP, PT_LOAD, PT_TLS},
+    elf::{
+        compression_header::{CompressionHeader, ELFCOMPRESS_ZLIB},
+        dynamic::{
+            df_tag_to_str, tag_to_str, DT_FLAGS, DT_NEEDED, DT_NULL, DT_RPATH, DT_RUNPATH,
+            DT_SONAME,
+        },
+        note::{NT_GNU_ABI_TAG, NT_GNU_BUILD_ID, NT_GNU_GOLD_VERSION},
+        reloc::{r_to_str, Reloc, RelocSection},
+        section_header::{
+            shf_to_str, sht_to_str, SHF_ALLOC, SHF_COMPRESSED, SHF_EXECINSTR, SHF_FLAGS,
+            SHF_INFO_LINK, SHF_TLS, SHF_WRITE, SHN_UNDEF, SHN_XINDEX, SHT_DYNAMIC, SHT_DYNSYM,
+            SHT_GNU_HASH, SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM, SHT_GROUP, SHT_HASH,
+            SHT_NOBITS, SHT_NOTE, SHT_NULL, SHT_REL, SHT_RELA, SHT_STRTAB, SHT_SYMTAB,
+            SHT_SYMTAB_SHNDX,
+        },
+        sym::{bind_to_str, type_to_str, Symtab, STB_LOCAL},
+        Elf, SectionHeader,
+    },
+};
+use std::{
+    cmp,
+    collections::HashMap,
+    env::current_exe,
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
     },
-    elf64::{header::Header, section_header::SectionHeader},
 };
-use std::{env::current_exe, path::Path};
 
+/// A loaded executable, described as both its layout in the file and its layout in memory.
 pub struct ExecutableFile {
     pub file_root: FileNode,
     pub ram_root: FileNode,
     pub inspector_collapsed: bool,
     pub name: String,
+    /// The raw bytes of the file, kept around for features that need to inspect section contents.
+    pub data: Arc<[u8]>,
+    /// Address where execution starts, in the same address space as the RAM layout.
+    /// Only read from ELF files.
+    pub entry_point: Option<u64>,
+    /// Computed on first use by [`ExecutableFile::entropy`].
+    entropy: OnceLock<Arc<EntropyProfile>>,
+}
+
+/// Everything but the raw bytes, so the output can be shared without sharing the file.
+impl fmt::Debug for ExecutableFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutableFile")
+            .field("name", &self.name)
+            .field("inspector_collapsed", &self.inspector_collapsed)
+            .field("data", &format_args!("<{} bytes>", self.data.len()))
+            .field("entry_point", &self.entry_point)
+            .field("file_root", &self.file_root)
+            .field("ram_root", &self.ram_root)
+            .finish()
+    }
+}
+
+/// Which of the layouts of an [`ExecutableFile`] a tree of [`FileNode`]s describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Layout {
+    /// [`ExecutableFile::file_root`], the layout of the file on disk.
+    #[default]
+    File,
+    /// [`ExecutableFile::ram_root`], the layout of the executable once loaded into memory.
+    Ram,
 }
 
 impl ExecutableFile {
+    pub fn root(&self, layout: Layout) -> &FileNode {
+        match layout {
+            Layout::File => &self.file_root,
+            Layout::Ram => &self.ram_root,
+        }
+    }
+
     pub fn load_self() -> Self {
         Self::load(&current_exe().unwrap()).unwrap()
     }
 
     pub fn load(path: &Path) -> Result<Self> {
-        let file_bytes = std::fs::read(path).unwrap();
-        let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+        let file_bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
         Self::load_from_bytes(name, &file_bytes)
     }
 
     pub fn load_from_bytes(name: String, data: &[u8]) -> Result<Self> {
+        Self::load_from_bytes_cancellable(name, data, &AtomicBool::new(false))
+    }
+
+    /// The entropy of [`ExecutableFile::data`], computed on first use and then cached.
+    /// Shared so it can be read while the trees are being modified.
+    pub fn entropy(&self) -> Arc<EntropyProfile> {
+        self.entropy
+            .get_or_init(|| Arc::new(EntropyProfile::new(&self.data)))
+            .clone()
+    }
+
+    /// Iterates over the leaves of [`ExecutableFile::file_root`] in file order, skipping headers and gaps.
+    /// Each node has its file range in `file_bytes_*`, its memory range in `ram_bytes_*` and its type in `ty`.
+    ///
+    /// Leaves are the smallest known regions, e.g. the sections within a segment rather than the segment.
+    /// Tables are yielded as a whole rather than their entries, see [`FileNode::has_entries`],
+    /// so a symbol table is yielded but not its symbols.
+    /// Parts of the file not described by any section, the [`SectionType::Gap`] nodes, are not yielded,
+    /// use [`ExecutableFile::sections_and_gaps_iter`] to include them.
+    pub fn sections_iter(&self) -> SectionsIter<'_> {
+        SectionsIter {
+            stack: vec![&self.file_root],
+            gaps: false,
+        }
+    }
+
+    /// Same as [`ExecutableFile::sections_iter`] but also yields the [`SectionType::Gap`] nodes between the sections,
+    /// so the file ranges of the nodes together cover every byte not taken by headers.
+    pub fn sections_and_gaps_iter(&self) -> SectionsIter<'_> {
+        SectionsIter {
+            stack: vec![&self.file_root],
+            gaps: true,
+        }
+    }
+
+    /// Each pair of sibling nodes in [`ExecutableFile::file_root`] whose byte ranges overlap.
+    /// Sections sharing bytes of the file usually mean the file is corrupt or was crafted to confuse tools.
+    ///
+    /// Only siblings are compared, nodes always overlap their parents.
+    pub fn overlapping_sections(&self) -> Vec<(&FileNode, &FileNode)> {
+        self.file_root
+            .iter()
+            .flat_map(|node| {
+                node.overlapping_children()
+                    .into_iter()
+                    .map(|(a, b)| (&node.children[a], &node.children[b]))
+            })
+            .collect()
+    }
+
+    /// True if the file has neither a `.symtab` nor any `.debug_*` sections, so only exported symbols have names.
+    /// `None` if the file has no ELF sections to tell either way, e.g. a raw image.
+    pub fn is_stripped(&self) -> Option<bool> {
+        let mut sections = self
+            .file_root
+            .iter()
+            .filter(|node| matches!(node.ty, SectionType::ElfSection { .. }))
+            .peekable();
+        sections.peek()?;
+        Some(!sections.any(|node| {
+            node.name == ".symtab"
+                || node.name.starts_with(".debug_")
+                || node.name.starts_with(".zdebug_")
+        }))
+    }
+
+    /// Loads a file without any executable structure, such as a bare-metal firmware dump, as a single [`SectionType::Raw`] node.
+    /// The file is placed at `base_address` in memory so that the RAM layout shows the real memory range.
+    pub fn load_raw(name: String, data: &[u8], base_address: u64) -> Result<Self> {
+        let len = data.len() as u64;
+        let ram_bytes_end = checked_end(base_address, len).with_context(|| {
+            format!("A 0x{len:x} byte image does not fit at base address 0x{base_address:x}")
+        })?;
+        let raw = FileNode {
+            name: "Raw".into(),
+            bytes_start: 0,
+            bytes_end: len,
+            ram_bytes_start: base_address,
+            ram_bytes_end,
+            file_bytes_start: 0,
+            file_bytes_end: len,
+            children: vec![],
+            notes: vec![("base address".into(), format!("0x{base_address:x}"))],
+            ty: SectionType::Raw,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        let file_root = FileNode {
+            name: "Raw file".into(),
+            bytes_start: 0,
+            bytes_end: len,
+            ram_bytes_start: base_address,
+            ram_bytes_end,
+            file_bytes_start: 0,
+            file_bytes_end: len,
+            notes: vec![],
+            children: vec![raw.clone()],
+            ty: SectionType::Root,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        let ram_root = FileNode {
+            name: "RAM".into(),
+            bytes_start: 0,
+            bytes_end: ram_bytes_end,
+            ram_bytes_start: 0,
+            ram_bytes_end,
+            file_bytes_start: 0,
+            file_bytes_end: len,
+            notes: vec![],
+            children: vec![raw.ram_layout()],
+            ty: SectionType::Root,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        Ok(ExecutableFile {
+            name,
+            file_root,
+            ram_root,
+            inspector_collapsed: false,
+            data: data.into(),
+            entry_point: None,
+            entropy: OnceLock::new(),
+        })
+    }
+
+    /// Loads an executable embedded within a larger file, e.g. a firmware image.
+    /// `data` is the whole container file, the executable starts at `offset` and is `len` bytes long, or extends to the end of `data` if `len` is `None`.
+    ///
+    /// All offsets in the returned file are relative to the start of the embedded executable.
+    pub fn load_embedded(name: String, data: &[u8], offset: u64, len: Option<u64>) -> Result<Self> {
+        Self::load_embedded_cancellable(name, data, offset, len, &AtomicBool::new(false))
+    }
+
+    /// Same as [`ExecutableFile::load_embedded`] but parsing is abandoned with an error soon after `cancel` is set.
+    pub fn load_embedded_cancellable(
+        name: String,
+        data: &[u8],
+        offset: u64,
+        len: Option<u64>,
+        cancel: &AtomicBool,
+    ) -> Result<Self> {
+        let data_len = data.len() as u64;
+        if offset > data_len {
+            return Err(anyhow!(
+                "Offset 0x{offset:x} is past the end of the 0x{data_len:x} byte file."
+            ));
+        }
+        let end = match len {
+            Some(len) => offset
+                .checked_add(len)
+                .filter(|end| *end <= data_len)
+                .ok_or_else(|| {
+                    anyhow!("0x{len:x} bytes at offset 0x{offset:x} extends past the end of the 0x{data_len:x} byte file.")
+                })?,
+            None => data_len,
+        };
+
+        let mut file =
+            Self::load_from_bytes_cancellable(name, &data[offset as usize..end as usize], cancel)
+                .with_context(|| {
+                format!("Failed to load executable embedded at offset 0x{offset:x}")
+            })?;
+        file.file_root
+            .notes
+            .push(("offset in container".into(), format!("0x{offset:x}")));
+        Ok(file)
+    }
+
+    /// Same as [`ExecutableFile::load_from_bytes`] but parsing is abandoned with an error soon after `cancel` is set.
+    pub fn load_from_bytes_cancellable(
+        name: String,
+        data: &[u8],
+        cancel: &AtomicBool,
+    ) -> Result<Self> {
+        let mut file = match ExecutableFormat::detect(data) {
+            Some(ExecutableFormat::Pe) => pe::load(name, data, cancel),
+            Some(ExecutableFormat::MachO) => macho::load(name, data, cancel),
+            Some(ExecutableFormat::MachOFat) => macho::load_fat(name, data, cancel),
+            Some(ExecutableFormat::Wasm) => wasm::load(name, data, cancel),
+            Some(ExecutableFormat::Archive) => archive::load(name, data, cancel),
+            // Let the ELF loader report what is wrong with the magic bytes.
+            Some(ExecutableFormat::Elf) | None => Self::load_elf(name, data, cancel),
+        }?;
+        // In memory overlaps are expected, e.g. `.tbss` shares addresses with the sections following it.
+        file.file_root.mark_overlaps();
+        Ok(file)
+    }
+
+    fn load_elf(name: String, data: &[u8], cancel: &AtomicBool) -> Result<Self> {
         if data.len() < 4 || data[0..4] != [0x7f, b'E', b'L', b'F'] {
             return Err(anyhow!("Magic ELF bytes were wrong."));
         }
-        let header = Header::parse(data).unwrap();
+        // 32-bit headers are widened into the same types as 64-bit headers.
+        let header = Elf::parse_header(data).context("ELF header is malformed")?;
+        let ctx = Ctx::new(header.container()?, header.endianness()?);
 
         let mut file_children = vec![];
         let mut ram_children = vec![];
@@ -45,10 +328,16 @@ impl ExecutableFile {
             children: vec![],
             notes: vec![],
             ty: SectionType::ElfHeader,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
         });
         for i in 0..header.e_phnum {
-            let bytes_start = header.e_phoff + i as u64 * header.e_phentsize as u64;
-            let bytes_end = header.e_phoff + (i as u64 + 1) * header.e_phentsize as u64;
+            let (bytes_start, bytes_end) =
+                table_entry(header.e_phoff, i as u64, header.e_phentsize as u64)
+                    .with_context(|| format!("Program header {i} is malformed"))?;
             file_children.push(FileNode {
                 name: format!("Program Header Segment #{i}"),
                 bytes_start,
@@ -60,26 +349,71 @@ impl ExecutableFile {
                 children: vec![],
                 notes: vec![],
                 ty: SectionType::ElfProgramHeader,
+                links: vec![],
+                content_hash: OnceLock::new(),
+                occurrence: 0,
+                overlaps_sibling: false,
+                inspector_collapsed: false,
             });
         }
 
         // The program headers will point at parts of the file, telling the os which parts to load into specific locations in memory.
-        // We dont parse or take that into account at all since that is just a subset of the data defined by the elf sections.
+        // That is mostly a subset of the data defined by the elf sections, so the loadable segments only group the sections in the file layout,
+        // and the TLS segment groups its sections in the RAM layout, see `group_tls`.
+        let program_headers =
+            ProgramHeader::parse(data, header.e_phoff as usize, header.e_phnum as usize, ctx)
+                .context("Program headers are malformed")?;
 
+        // With 0xff00 or more sections `e_shnum` is 0 and the count is in `sh_size` of the first section header instead,
+        // `SectionHeader::parse` takes care of that.
         let section_headers =
-            SectionHeader::from_bytes(&data[header.e_shoff as usize..], header.e_shnum as usize);
-        let str_table_header = section_headers[header.e_shstrndx as usize];
-        let str_table_start = str_table_header.sh_offset as usize;
-        let str_table_end = str_table_start + str_table_header.sh_size as usize;
-        let section_name_table = &data[str_table_start..str_table_end];
+            SectionHeader::parse(data, header.e_shoff as usize, header.e_shnum as usize, ctx)
+                .with_context(|| {
+                    format!("Section headers at 0x{:x} are malformed", header.e_shoff)
+                })?;
+        // Files without section headers also have no section name table.
+        // Likewise a section name table index that doesn't fit is in `sh_link` of the first section header.
+        let shstrndx = match (header.e_shstrndx as u32, section_headers.first()) {
+            (SHN_XINDEX, Some(first)) => first.sh_link as usize,
+            (shstrndx, _) => shstrndx as usize,
+        };
+        let section_name_table = match section_headers.get(shstrndx) {
+            Some(str_table_header) => section_data(data, str_table_header),
+            None if section_headers.is_empty() => &[],
+            None => {
+                return Err(anyhow!(
+                "Section name table index {shstrndx} is out of bounds of the {} section headers.",
+                section_headers.len()
+            ))
+            }
+        };
+
+        // Notes about the whole file found while loading the sections, e.g. the build id.
+        let mut root_notes = vec![];
+        // Object files and most shared libraries have no entry point, which is stored as 0.
+        let entry_point = (header.e_entry != 0).then_some(header.e_entry);
+        if let Some(entry_point) = entry_point {
+            root_notes.push(("entry point".into(), format!("0x{entry_point:x}")));
+        }
+        // Dynamically linked executables name the dynamic loader that runs them, it points at `.interp`.
+        if let Some(interp) = program_headers.iter().find(|ph| ph.p_type == PT_INTERP) {
+            let start = interp.p_offset as usize;
+            let end = start.saturating_add(interp.p_filesz as usize);
+            let path = match data.get(start..end) {
+                Some(path) => interpreter_path(path),
+                None => "PT_INTERP is out of bounds of the file".to_owned(),
+            };
+            root_notes.push(("interpreter".into(), path));
+        }
 
         // These headers are usually at the very end of the file
         let section_headers_start = header.e_shoff;
 
         for (i, section_header) in section_headers.iter().enumerate() {
             let name = parse_str_table(section_name_table, section_header.sh_name);
-            let bytes_start = section_headers_start + i as u64 * header.e_shentsize as u64;
-            let bytes_end = section_headers_start + (i as u64 + 1) * header.e_shentsize as u64;
+            let (bytes_start, bytes_end) =
+                table_entry(section_headers_start, i as u64, header.e_shentsize as u64)
+                    .with_context(|| format!("Section header {i} for {name} is malformed"))?;
             file_children.push(FileNode {
                 name: format!("ELF Section Header for {name}"),
                 bytes_start,
@@ -91,84 +425,30 @@ impl ExecutableFile {
                 children: vec![],
                 notes: vec![],
                 ty: SectionType::ElfSectionHeader,
+                links: vec![],
+                content_hash: OnceLock::new(),
+                occurrence: 0,
+                overlaps_sibling: false,
+                inspector_collapsed: false,
             });
         }
 
-        for section_header in &section_headers {
-            // https://docs.oracle.com/cd/E19683-01/816-1386/chapter6-94076/index.html
-
-            let ty = sht_to_str(section_header.sh_type).to_owned();
-            let mut flags = String::new();
-            for flag in SHF_FLAGS {
-                if section_header.sh_flags & flag as u64 != 0 {
-                    if !flags.is_empty() {
-                        flags.push('|')
-                    }
-                    flags.push_str(shf_to_str(flag));
-                }
-            }
-            if flags.is_empty() {
-                flags = "NONE".to_owned();
-            }
-
-            let ram_bytes_start = section_header.sh_addr;
-            let ram_bytes_end = section_header.sh_addr + section_header.sh_size;
-            let address_alignment = format!("0x{:x}", section_header.sh_addralign);
-            let mut notes = vec![
-                ("type".into(), ty),
-                ("flags".into(), flags),
-                ("address alignment".into(), address_alignment),
-            ];
-
-            // TODO: and many other sh_link handling https://docs.oracle.com/cd/E19683-01/816-1386/6m7qcoblj/index.html#chapter6-47976
-            let link_name = section_headers
-                .get(section_header.sh_link as usize)
-                .map(|section_header| parse_str_table(section_name_table, section_header.sh_name))
-                .unwrap_or_else(|| "bad link section".to_owned());
-            if section_header.sh_type == SHT_DYNAMIC {
-                notes.push(("string table in section".into(), link_name));
-            } else if section_header.sh_type == SHT_REL || section_header.sh_type == SHT_RELA {
-                notes.push(("symbol table in section".into(), link_name));
-            }
-
-            let file_bytes_start = section_header.sh_offset;
-            let file_bytes_end = section_header.sh_offset + section_header.sh_size;
-            let name = parse_str_table(section_name_table, section_header.sh_name);
-
-            if section_header.sh_flags & SHF_ALLOC as u64 != 0 {
-                ram_children.push(FileNode {
-                    name: name.clone(),
-                    bytes_start: ram_bytes_start,
-                    bytes_end: ram_bytes_end,
-                    ram_bytes_start,
-                    ram_bytes_end,
-                    file_bytes_start,
-                    file_bytes_end,
-                    children: vec![],
-                    notes: notes.clone(),
-                    ty: SectionType::ElfSectionHeader,
-                });
-            }
-            if section_header.sh_type != SHT_NOBITS && section_header.sh_type != SHT_NULL {
-                file_children.push(FileNode {
-                    name,
-                    bytes_start: file_bytes_start,
-                    bytes_end: file_bytes_end,
-                    ram_bytes_start,
-                    ram_bytes_end,
-                    file_bytes_start,
-                    file_bytes_end,
-                    children: vec![],
-                    notes,
-                    ty: SectionType::ElfSectionHeader,
-                });
-            }
+        let sections = load_elf_sections(
+            data,
+            &section_headers,
+            section_name_table,
+            header.e_machine,
+            ctx,
+            cancel,
+        )?;
+        for section in sections {
+            root_notes.extend(section.root_notes);
+            ram_children.extend(section.ram);
+            file_children.extend(section.file);
         }
-        for child in &file_children {
-            for other_child in &file_children {
-                if child.overlaps(other_child) {
-                    println!("WARN: Overlapping children found: {child:?} {other_child:?}");
-                }
+        for (i, segment) in program_headers.iter().enumerate() {
+            if segment.p_type == PT_LOAD {
+                group_load_segment(i, segment, &mut file_children)?;
             }
         }
 
@@ -180,13 +460,24 @@ impl ExecutableFile {
             ram_bytes_end: 0, // TODO
             file_bytes_start: 0,
             file_bytes_end: data.len() as u64,
-            notes: vec![],
+            notes: root_notes,
             children: file_children,
             ty: SectionType::Root,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
         };
         file_root.sort();
+        file_root.fill_gaps();
+        file_root.number_occurrences();
+
+        if let Some(tls) = program_headers.iter().find(|ph| ph.p_type == PT_TLS) {
+            group_tls(tls, &mut ram_children)?;
+        }
 
-        let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap();
+        let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap_or(0);
         let mut ram_root = FileNode {
             name: "RAM".into(),
             bytes_start: 0,
@@ -198,29 +489,1123 @@ impl ExecutableFile {
             notes: vec![],
             children: ram_children,
             ty: SectionType::Root,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
         };
         ram_root.sort();
+        ram_root.number_occurrences();
 
         Ok(ExecutableFile {
             name,
             file_root,
             ram_root,
             inspector_collapsed: false,
+            data: data.into(),
+            entry_point,
+            entropy: OnceLock::new(),
+        })
+    }
+}
+
+/// Executable file formats that can be recognized by their magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutableFormat {
+    Elf,
+    /// Windows PE/COFF, e.g. `.exe` and `.dll`
+    Pe,
+    MachO,
+    /// A universal binary containing Mach-O files for multiple architectures.
+    MachOFat,
+    Wasm,
+    /// A static library, containing object files.
+    Archive,
+}
+
+impl ExecutableFormat {
+    /// Detect the format of the executable starting at the beginning of `data`.
+    pub fn detect(data: &[u8]) -> Option<ExecutableFormat> {
+        let magic = data.get(0..4)?;
+        if magic == b"\x7fELF" {
+            // Also check the class and endianness to avoid matching random bytes.
+            if matches!(data.get(4), Some(1 | 2)) && matches!(data.get(5), Some(1 | 2)) {
+                return Some(ExecutableFormat::Elf);
+            }
+        } else if magic[0..2] == *b"MZ" {
+            // The DOS header points at the PE header via e_lfanew.
+            let pe_offset = data.get(0x3c..0x40)?;
+            let pe_offset = u32::from_le_bytes(pe_offset.try_into().unwrap()) as usize;
+            if data.get(pe_offset..pe_offset.checked_add(4)?)? == b"PE\0\0" {
+                return Some(ExecutableFormat::Pe);
+            }
+        } else if matches!(
+            magic,
+            [0xfe, 0xed, 0xfa, 0xce | 0xcf] | [0xce | 0xcf, 0xfa, 0xed, 0xfe]
+        ) {
+            return Some(ExecutableFormat::MachO);
+        } else if magic == [0xca, 0xfe, 0xba, 0xbe] {
+            // Java class files share this magic, but they follow it with a version number much larger than any realistic architecture count.
+            let count = u32::from_be_bytes(data.get(4..8)?.try_into().unwrap());
+            if count > 0 && count < 20 {
+                return Some(ExecutableFormat::MachOFat);
+            }
+        } else if magic == b"\0asm" && data.get(4..8)? == [1, 0, 0, 0] {
+            return Some(ExecutableFormat::Wasm);
+        } else if data.starts_with(archive::MAGIC) {
+            return Some(ExecutableFormat::Archive);
+        }
+        None
+    }
+}
+
+/// An executable found within a larger file by [`find_embedded_executables`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbeddedExecutable {
+    pub offset: u64,
+    pub format: ExecutableFormat,
+}
+
+/// Scans `data` for the magic bytes of every [`ExecutableFormat`], e.g. to find the executables bundled in a firmware image or installer.
+/// Each hit can be loaded with [`ExecutableFile::load_embedded`], but some may be false positives.
+pub fn find_embedded_executables(data: &[u8]) -> Vec<EmbeddedExecutable> {
+    (0..data.len())
+        .filter(|&i| matches!(data[i], 0x7f | b'M' | 0xfe | 0xce | 0xcf | 0xca | 0 | b'!'))
+        .filter_map(|i| {
+            ExecutableFormat::detect(&data[i..]).map(|format| EmbeddedExecutable {
+                offset: i as u64,
+                format,
+            })
         })
+        .collect()
+}
+
+impl TryFrom<&Path> for ExecutableFile {
+    type Error = Error;
+
+    /// Same as [`ExecutableFile::load`].
+    fn try_from(path: &Path) -> Result<Self> {
+        Self::load(path)
     }
 }
 
-fn parse_str_table(data: &[u8], offset: u32) -> String {
-    if offset as usize > data.len() {
+impl TryFrom<&[u8]> for ExecutableFile {
+    type Error = Error;
+
+    /// Same as [`ExecutableFile::load_from_bytes`] but with the placeholder name `<bytes>`.
+    fn try_from(data: &[u8]) -> Result<Self> {
+        Self::load_from_bytes("<bytes>".to_owned(), data)
+    }
+}
+
+fn parse_str_table(data: &[u8], offset: usize) -> String {
+    if offset > data.len() {
         return "sh_name out of bounds of string table".to_owned();
     }
-    std::ffi::CStr::from_bytes_until_nul(&data[offset as usize..])
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_owned()
+    match std::ffi::CStr::from_bytes_until_nul(&data[offset..]) {
+        Ok(name) => name.to_string_lossy().into_owned(),
+        Err(_) => "name not terminated in string table".to_owned(),
+    }
+}
+
+/// The path to the dynamic loader stored in `.interp`, which should be nul terminated but is read up to the end of `data` if it isn't.
+fn interpreter_path(data: &[u8]) -> String {
+    let path = data.split(|byte| *byte == 0).next().unwrap_or_default();
+    String::from_utf8_lossy(path).into_owned()
+}
+
+/// The bytes of a section in the file, or an empty slice if the section lies outside of the file.
+fn section_data<'a>(data: &'a [u8], section_header: &SectionHeader) -> &'a [u8] {
+    let start = section_header.sh_offset as usize;
+    let end = start.saturating_add(section_header.sh_size as usize);
+    data.get(start..end).unwrap_or(&[])
+}
+
+/// What [`ExecutableFile::load_elf`] makes of a single section header.
+struct ElfSection {
+    /// The node of the section in the RAM layout, if it is loaded into memory.
+    ram: Option<FileNode>,
+    /// The node of the section in the file layout, if it takes up space in the file.
+    file: Option<FileNode>,
+    /// Notes about the whole file found in the section, e.g. the build id.
+    root_notes: Vec<(String, String)>,
+}
+
+/// Loads the section of each of `section_headers`, in the same order.
+/// Sections only read the file, so with the `parallel` feature they are loaded on all cores,
+/// which matters for files with tens of thousands of symbols or relocations.
+/// Without it, and always on the web, they are loaded one after the other.
+fn load_elf_sections(
+    data: &[u8],
+    section_headers: &[SectionHeader],
+    section_name_table: &[u8],
+    e_machine: u16,
+    ctx: Ctx,
+    cancel: &AtomicBool,
+) -> Result<Vec<ElfSection>> {
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    use rayon::prelude::*;
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    let iter = section_headers.par_iter();
+    #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+    let iter = section_headers.iter();
+
+    iter.map(|section_header| {
+        load_elf_section(
+            data,
+            section_headers,
+            section_name_table,
+            section_header,
+            e_machine,
+            ctx,
+            cancel,
+        )
+    })
+    .collect()
+}
+
+/// Loads the section of `section_header` along with its symbols, relocations or other entries.
+fn load_elf_section(
+    data: &[u8],
+    section_headers: &[SectionHeader],
+    section_name_table: &[u8],
+    section_header: &SectionHeader,
+    e_machine: u16,
+    ctx: Ctx,
+    cancel: &AtomicBool,
+) -> Result<ElfSection> {
+    check_cancelled(cancel)?;
+    // https://docs.oracle.com/cd/E19683-01/816-1386/chapter6-94076/index.html
+
+    let ty = sht_to_str(section_header.sh_type).to_owned();
+    let mut flags = String::new();
+    for flag in SHF_FLAGS {
+        if section_header.sh_flags & flag as u64 != 0 {
+            if !flags.is_empty() {
+                flags.push('|')
+            }
+            flags.push_str(shf_to_str(flag));
+        }
+    }
+    if flags.is_empty() {
+        flags = "NONE".to_owned();
+    }
+
+    let name = parse_str_table(section_name_table, section_header.sh_name);
+    let ram_bytes_start = section_header.sh_addr;
+    let ram_bytes_end = checked_end(section_header.sh_addr, section_header.sh_size)
+        .with_context(|| format!("Address range of section {name} is malformed"))?;
+    let address_alignment = format!("0x{:x}", section_header.sh_addralign);
+    let mut notes = vec![
+        ("type".into(), ty),
+        ("flags".into(), flags),
+        ("address alignment".into(), address_alignment),
+    ];
+    // Only table-like sections have an entry size, it is 0 for the rest.
+    if section_header.sh_entsize != 0 && section_header.sh_type != SHT_NOBITS {
+        let count = section_header.sh_size / section_header.sh_entsize;
+        notes.push((
+            "entries".into(),
+            format!("{count} × {} bytes", section_header.sh_entsize),
+        ));
+    }
+
+    let mut links = vec![];
+    for (relation, index) in section_link_indexes(section_header) {
+        match section_headers.get(index as usize) {
+            Some(target) => {
+                let target_name = parse_str_table(section_name_table, target.sh_name);
+                notes.push((format!("{relation} section"), target_name.clone()));
+                links.push(SectionLink {
+                    relation,
+                    name: target_name,
+                    file_bytes_start: target.sh_offset,
+                });
+            }
+            None => notes.push((
+                format!("{relation} section"),
+                format!("bad link section {index}"),
+            )),
+        }
+    }
+
+    let file_bytes_start = section_header.sh_offset;
+    let file_bytes_end = checked_end(section_header.sh_offset, section_header.sh_size)
+        .with_context(|| format!("File range of section {name} is malformed"))?;
+
+    let section_type = SectionType::ElfSection {
+        sh_type: section_header.sh_type,
+        sh_flags: section_header.sh_flags,
+    };
+
+    let mut children = vec![];
+    let mut build_id_notes = vec![];
+    if matches!(section_header.sh_type, SHT_SYMTAB | SHT_DYNSYM) {
+        match symbol_table(
+            data,
+            section_headers,
+            section_name_table,
+            section_header,
+            ctx,
+            cancel,
+        ) {
+            Ok(symbols) => children = symbols,
+            Err(err) => notes.push(("failed to parse symbols".into(), format!("{err}"))),
+        }
+    }
+    if matches!(section_header.sh_type, SHT_REL | SHT_RELA) {
+        match relocations(
+            data,
+            section_headers,
+            section_header,
+            e_machine,
+            ctx,
+            cancel,
+        ) {
+            Ok(entries) => children = entries,
+            Err(err) => notes.push(("failed to parse relocations".into(), format!("{err}"))),
+        }
+    }
+    if section_header.sh_type == SHT_NOTE {
+        match elf_notes(
+            section_data(data, section_header),
+            section_header.sh_addralign,
+            ctx,
+        ) {
+            Ok(elf_notes) => {
+                build_id_notes.extend(
+                    elf_notes
+                        .iter()
+                        .filter(|(key, _)| key.ends_with("build id"))
+                        .cloned(),
+                );
+                notes.extend(elf_notes);
+            }
+            Err(err) => notes.push(("failed to parse notes".into(), format!("{err}"))),
+        }
+    }
+    if section_header.sh_type == SHT_DYNAMIC {
+        match dynamic_entries(data, section_headers, section_header, ctx, cancel) {
+            Ok(entries) => children = entries,
+            Err(err) => notes.push(("failed to parse entries".into(), format!("{err}"))),
+        }
+    }
+    if section_header.sh_flags & SHF_COMPRESSED as u64 != 0 {
+        match compression_notes(data, section_header, ctx) {
+            Ok(compression) => notes.extend(compression),
+            Err(err) => notes.push((
+                "failed to parse compression header".into(),
+                format!("{err}"),
+            )),
+        }
+    }
+    if name == ".interp" {
+        let path = interpreter_path(section_data(data, section_header));
+        notes.push(("interpreter".into(), path));
+    }
+    if name == ".eh_frame" {
+        match eh_frame_entries(section_data(data, section_header), ctx) {
+            Ok((cies, fdes)) => {
+                notes.push(("unwind entries (FDEs)".into(), fdes.to_string()));
+                notes.push(("CIEs".into(), cies.to_string()));
+            }
+            Err(err) => notes.push(("failed to parse unwind entries".into(), format!("{err}"))),
+        }
+    } else if name == ".eh_frame_hdr" {
+        match eh_frame_hdr_fde_count(section_data(data, section_header), ctx) {
+            Ok(fde_count) => notes.push(("FDEs in search table".into(), fde_count.to_string())),
+            Err(err) => notes.push(("failed to parse header".into(), format!("{err}"))),
+        }
+    }
+
+    let ram = (section_header.sh_flags & SHF_ALLOC as u64 != 0).then(|| FileNode {
+        name: name.clone(),
+        bytes_start: ram_bytes_start,
+        bytes_end: ram_bytes_end,
+        ram_bytes_start,
+        ram_bytes_end,
+        file_bytes_start,
+        file_bytes_end,
+        children: children.iter().map(FileNode::ram_layout).collect(),
+        notes: notes.clone(),
+        ty: section_type.clone(),
+        links: links.clone(),
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    });
+    let file = (section_header.sh_type != SHT_NOBITS && section_header.sh_type != SHT_NULL)
+        .then_some(FileNode {
+            name,
+            bytes_start: file_bytes_start,
+            bytes_end: file_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            children,
+            notes,
+            ty: section_type,
+            links,
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+    Ok(ElfSection {
+        ram,
+        file,
+        root_notes: build_id_notes,
+    })
+}
+
+/// Moves the thread local sections out of `ram_children` into a node describing the TLS block of the `tls` segment.
+///
+/// Each thread gets its own copy of the block: `.tdata` is copied from the file and `.tbss` after it is zeroed.
+/// `.tbss` takes up no space in the loaded image, so the block overlaps the sections following `.tdata`.
+fn group_tls(tls: &ProgramHeader, ram_children: &mut Vec<FileNode>) -> Result<()> {
+    let bytes_end = checked_end(tls.p_vaddr, tls.p_memsz).context("TLS segment is malformed")?;
+    let file_bytes_end =
+        checked_end(tls.p_offset, tls.p_filesz).context("TLS segment is malformed")?;
+    let is_tls = |node: &FileNode| match node.ty {
+        SectionType::ElfSection { sh_flags, .. } => sh_flags & SHF_TLS as u64 != 0,
+        _ => false,
+    };
+    let (children, rest) = std::mem::take(ram_children).into_iter().partition(is_tls);
+    *ram_children = rest;
+
+    let zeroed = tls.p_memsz.saturating_sub(tls.p_filesz);
+    // The block of each thread is padded out to the alignment.
+    let padded = tls
+        .p_memsz
+        .checked_next_multiple_of(tls.p_align.max(1))
+        .unwrap_or(tls.p_memsz);
+    ram_children.push(FileNode {
+        name: "TLS block".into(),
+        bytes_start: tls.p_vaddr,
+        bytes_end,
+        ram_bytes_start: tls.p_vaddr,
+        ram_bytes_end: bytes_end,
+        file_bytes_start: tls.p_offset,
+        file_bytes_end,
+        notes: vec![
+            ("size per thread".into(), format!("0x{:x}", tls.p_memsz)),
+            ("alignment".into(), format!("0x{:x}", tls.p_align)),
+            ("size padded to alignment".into(), format!("0x{padded:x}")),
+            (
+                "initialized (.tdata)".into(),
+                format!("0x{:x}", tls.p_filesz),
+            ),
+            ("zero initialized (.tbss)".into(), format!("0x{zeroed:x}")),
+        ],
+        children,
+        ty: SectionType::TlsBlock,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    });
+    Ok(())
+}
+
+/// Moves the nodes within the file range of the `PT_LOAD` program header `segment` out of `file_children` into a node for the segment.
+/// This is usually the ELF header, program headers and allocated sections, while the section headers and `.symtab` are left out.
+fn group_load_segment(
+    index: usize,
+    segment: &ProgramHeader,
+    file_children: &mut Vec<FileNode>,
+) -> Result<()> {
+    // Segments only in memory, e.g. one holding just `.bss`, have nothing to group in the file.
+    if segment.p_filesz == 0 {
+        return Ok(());
+    }
+    let file_bytes_end = checked_end(segment.p_offset, segment.p_filesz)
+        .with_context(|| format!("File range of LOAD segment #{index} is malformed"))?;
+    let ram_bytes_end = checked_end(segment.p_vaddr, segment.p_memsz)
+        .with_context(|| format!("Address range of LOAD segment #{index} is malformed"))?;
+    let (children, rest) =
+        std::mem::take(file_children)
+            .into_iter()
+            .partition(|node: &FileNode| {
+                !matches!(node.ty, SectionType::ElfSegment { .. })
+                    && segment.p_offset <= node.file_bytes_start
+                    && node.file_bytes_end <= file_bytes_end
+            });
+    *file_children = rest;
+
+    let mut notes = vec![
+        ("flags".into(), segment_flags_text(segment.p_flags)),
+        ("address".into(), format!("0x{:x}", segment.p_vaddr)),
+        ("alignment".into(), format!("0x{:x}", segment.p_align)),
+        ("size in file".into(), format!("0x{:x}", segment.p_filesz)),
+        ("size in memory".into(), format!("0x{:x}", segment.p_memsz)),
+    ];
+    // The rest of the segment is zeroed when loaded, this is where `.bss` goes.
+    if segment.p_memsz > segment.p_filesz {
+        notes.push((
+            "zero filled in memory".into(),
+            format!("0x{:x}", segment.p_memsz - segment.p_filesz),
+        ));
+    }
+    file_children.push(FileNode {
+        name: format!("LOAD segment #{index}"),
+        bytes_start: segment.p_offset,
+        bytes_end: file_bytes_end,
+        ram_bytes_start: segment.p_vaddr,
+        ram_bytes_end,
+        file_bytes_start: segment.p_offset,
+        file_bytes_end,
+        notes,
+        children,
+        ty: SectionType::ElfSegment {
+            p_flags: segment.p_flags,
+        },
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    });
+    Ok(())
+}
+
+/// Segment flags in the style of `ls`, e.g. `r-x`.
+fn segment_flags_text(p_flags: u32) -> String {
+    [(PF_R, 'r'), (PF_W, 'w'), (PF_X, 'x')]
+        .iter()
+        .map(|(flag, c)| if p_flags & flag != 0 { *c } else { '-' })
+        .collect()
+}
+
+/// The indexes of the sections that `section_header` refers to, along with what they are to it.
+///
+/// What `sh_link` and `sh_info` mean depends on the section type:
+/// https://docs.oracle.com/cd/E19683-01/816-1386/6m7qcoblj/index.html#chapter6-47976
+fn section_link_indexes(section_header: &SectionHeader) -> Vec<(&'static str, u32)> {
+    let link = section_header.sh_link;
+    let info = section_header.sh_info;
+    let mut links = match section_header.sh_type {
+        SHT_DYNAMIC | SHT_SYMTAB | SHT_DYNSYM | SHT_GNU_VERDEF | SHT_GNU_VERNEED => {
+            vec![("string table", link)]
+        }
+        SHT_HASH | SHT_GNU_HASH | SHT_SYMTAB_SHNDX | SHT_GROUP | SHT_GNU_VERSYM => {
+            vec![("symbol table", link)]
+        }
+        // sh_info is 0 for relocations that apply to the dynamic linker rather than one section.
+        SHT_REL | SHT_RELA if info != 0 => {
+            vec![("symbol table", link), ("relocated", info)]
+        }
+        SHT_REL | SHT_RELA => vec![("symbol table", link)],
+        _ => vec![],
+    };
+    let info_is_section = section_header.sh_flags & SHF_INFO_LINK as u64 != 0;
+    if info_is_section && !links.iter().any(|(_, index)| *index == info) {
+        links.push(("info", info));
+    }
+    links
+}
+
+/// The string table that `section_header` refers to via `sh_link`.
+///
+/// An ELF file usually has several string tables, e.g. `.shstrtab` for section names, `.strtab` for `.symtab` and `.dynstr` for `.dynsym`.
+/// Names in a section must only be looked up in the string table it links to.
+fn linked_str_table<'a>(
+    data: &'a [u8],
+    section_headers: &[SectionHeader],
+    section_header: &SectionHeader,
+) -> Result<&'a [u8]> {
+    let link = section_header.sh_link as usize;
+    let str_table = section_headers
+        .get(link)
+        .ok_or_else(|| anyhow!("sh_link {link} is out of bounds of the section headers"))?;
+    if str_table.sh_type != SHT_STRTAB {
+        return Err(anyhow!(
+            "sh_link {link} refers to a {} section instead of a string table",
+            sht_to_str(str_table.sh_type)
+        ));
+    }
+    Ok(section_data(data, str_table))
+}
+
+/// The start and end of entry `index` in a table of `entry_size` byte entries starting at `start`.
+/// Malformed files can describe tables that don't fit in a `u64`, that is an error rather than an overflow.
+fn table_entry(start: u64, index: u64, entry_size: u64) -> Result<(u64, u64)> {
+    index
+        .checked_mul(entry_size)
+        .and_then(|offset| start.checked_add(offset))
+        .and_then(|entry_start| Some((entry_start, entry_start.checked_add(entry_size)?)))
+        .ok_or_else(|| {
+            anyhow!("Entry {index} of the table at 0x{start:x} with 0x{entry_size:x} byte entries does not fit in 64 bits.")
+        })
+}
+
+/// The end of `len` bytes starting at `start`, or an error if that does not fit in a `u64`.
+fn checked_end(start: u64, len: u64) -> Result<u64> {
+    start
+        .checked_add(len)
+        .ok_or_else(|| anyhow!("0x{len:x} bytes at 0x{start:x} does not fit in 64 bits."))
+}
+
+/// Counts the CIEs and FDEs in an `.eh_frame` section, without decoding their contents.
+/// https://refspecs.linuxfoundation.org/LSB_5.0.0/LSB-Core-generic/LSB-Core-generic/ehframechpt.html
+fn eh_frame_entries(data: &[u8], ctx: Ctx) -> Result<(u64, u64)> {
+    let read_u32 = |offset: usize| -> Result<u32> {
+        let bytes: [u8; 4] = data
+            .get(offset..offset.saturating_add(4))
+            .ok_or_else(|| anyhow!("Entry at 0x{offset:x} is truncated"))?
+            .try_into()
+            .unwrap();
+        Ok(if ctx.is_little_endian() {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    let mut cies = 0;
+    let mut fdes = 0;
+    let mut offset = 0;
+    while offset < data.len() {
+        let (len, id_offset) = match read_u32(offset)? {
+            // A zero length entry terminates the section.
+            0 => break,
+            0xffff_ffff => {
+                let (first, second) = (read_u32(offset + 4)? as u64, read_u32(offset + 8)? as u64);
+                let len = if ctx.is_little_endian() {
+                    (second << 32) | first
+                } else {
+                    (first << 32) | second
+                };
+                (len, offset + 12)
+            }
+            len => (len as u64, offset + 4),
+        };
+        // The CIE id field is 0 for CIEs, otherwise it is the offset back to the CIE of this FDE.
+        if read_u32(id_offset)? == 0 {
+            cies += 1;
+        } else {
+            fdes += 1;
+        }
+        offset = usize::try_from(len)
+            .ok()
+            .and_then(|len| id_offset.checked_add(len))
+            .ok_or_else(|| anyhow!("Entry at 0x{offset:x} has an invalid length 0x{len:x}"))?;
+    }
+    Ok((cies, fdes))
+}
+
+/// Reads the number of FDEs in the binary search table of an `.eh_frame_hdr` section.
+fn eh_frame_hdr_fde_count(data: &[u8], ctx: Ctx) -> Result<u64> {
+    const DW_EH_PE_OMIT: u8 = 0xff;
+
+    let [version, eh_frame_ptr_enc, fde_count_enc, _table_enc, ..] = *data else {
+        return Err(anyhow!("Section is too small to contain a header"));
+    };
+    if version != 1 {
+        return Err(anyhow!("Unknown version {version}"));
+    }
+    if fde_count_enc == DW_EH_PE_OMIT {
+        return Err(anyhow!("Header has no search table"));
+    }
+    // Skip over eh_frame_ptr, only the size of its encoding matters.
+    let fde_count_offset = 4 + eh_pointer_size(eh_frame_ptr_enc, ctx)?;
+    let fde_count_size = eh_pointer_size(fde_count_enc, ctx)?;
+    let bytes = data
+        .get(fde_count_offset..fde_count_offset + fde_count_size)
+        .ok_or_else(|| anyhow!("Section is too small to contain fde_count"))?;
+    let mut value = [0; 8];
+    if ctx.is_little_endian() {
+        value[..fde_count_size].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    } else {
+        value[8 - fde_count_size..].copy_from_slice(bytes);
+        Ok(u64::from_be_bytes(value))
+    }
+}
+
+/// Size of a value stored with a `DW_EH_PE_*` pointer encoding.
+/// Only the fixed size formats are supported, which is all that `.eh_frame_hdr` uses in practice.
+fn eh_pointer_size(encoding: u8, ctx: Ctx) -> Result<usize> {
+    match encoding & 0x0f {
+        // absptr, the size of an address in the file.
+        0x00 => Ok(ctx.size()),
+        0x02 | 0x0a => Ok(2),
+        0x03 | 0x0b => Ok(4),
+        0x04 | 0x0c => Ok(8),
+        format => Err(anyhow!("Unsupported pointer encoding format 0x{format:x}")),
+    }
+}
+
+fn check_cancelled(cancel: &AtomicBool) -> Result<()> {
+    if cancel.load(Ordering::Relaxed) {
+        Err(anyhow!("Loading was cancelled."))
+    } else {
+        Ok(())
+    }
+}
+
+/// Describes how a `SHF_COMPRESSED` section is compressed, from the header at its start.
+/// The section size in the file is the compressed size, the header gives the size once decompressed, e.g. by a debugger.
+fn compression_notes(
+    data: &[u8],
+    section_header: &SectionHeader,
+    ctx: Ctx,
+) -> Result<Vec<(String, String)>> {
+    const ELFCOMPRESS_ZSTD: u32 = 2;
+
+    let header = CompressionHeader::parse(data, section_header.sh_offset as usize, ctx)?;
+    let algorithm = match header.ch_type {
+        ELFCOMPRESS_ZLIB => "zlib".to_owned(),
+        ELFCOMPRESS_ZSTD => "zstd".to_owned(),
+        ch_type => format!("unknown type {ch_type}"),
+    };
+    let ratio = header.ch_size as f64 / section_header.sh_size.max(1) as f64;
+    Ok(vec![
+        ("compression".into(), algorithm),
+        (
+            "compressed size".into(),
+            format!("0x{:x}", section_header.sh_size),
+        ),
+        (
+            "uncompressed size".into(),
+            format!("0x{:x} ({ratio:.1}× larger)", header.ch_size),
+        ),
+    ])
+}
+
+/// Decodes the well known entries of a `SHT_NOTE` section into notes for its node, e.g. the GNU build id.
+/// Unknown entries are listed by their owner and type.
+///
+/// Entries are aligned to `alignment`, which is 4 for almost all notes and 8 for `.note.gnu.property` in 64-bit files.
+fn elf_notes(data: &[u8], alignment: u64, ctx: Ctx) -> Result<Vec<(String, String)>> {
+    const NT_GO_BUILD_ID: u32 = 4;
+
+    let read_u32 = |offset: usize| -> Result<u32> {
+        let bytes: [u8; 4] = data
+            .get(offset..offset.saturating_add(4))
+            .ok_or_else(|| anyhow!("Note at 0x{offset:x} is truncated"))?
+            .try_into()
+            .unwrap();
+        Ok(if ctx.is_little_endian() {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    let alignment = if alignment == 8 { 8 } else { 4 };
+    // Both the description and the next entry start at an aligned offset.
+    let aligned_end = |start: usize, len: usize| {
+        start
+            .checked_add(len)
+            .and_then(|end| end.checked_next_multiple_of(alignment))
+    };
+
+    let mut notes = vec![];
+    let mut offset = 0;
+    while offset < data.len() {
+        let name_size = read_u32(offset)? as usize;
+        let desc_size = read_u32(offset + 4)? as usize;
+        let ty = read_u32(offset + 8)?;
+        let name_start = offset + 12;
+        let desc_start = aligned_end(name_start, name_size)
+            .ok_or_else(|| anyhow!("Note at 0x{offset:x} has an invalid name size"))?;
+        let name = data
+            .get(name_start..name_start + name_size)
+            .ok_or_else(|| anyhow!("Note at 0x{offset:x} is truncated"))?;
+        let desc = desc_start
+            .checked_add(desc_size)
+            .and_then(|desc_end| data.get(desc_start..desc_end))
+            .ok_or_else(|| anyhow!("Note at 0x{offset:x} is truncated"))?;
+        // The name includes its nul terminator.
+        let name = String::from_utf8_lossy(name.strip_suffix(&[0]).unwrap_or(name));
+
+        match (name.as_ref(), ty) {
+            ("GNU", NT_GNU_BUILD_ID) => {
+                let build_id = desc.iter().map(|byte| format!("{byte:02x}")).collect();
+                notes.push(("build id".into(), build_id));
+            }
+            // Go stores its own build id as text, alongside the GNU one when linked externally.
+            ("Go", NT_GO_BUILD_ID) => {
+                let build_id = String::from_utf8_lossy(desc).into_owned();
+                notes.push(("Go build id".into(), build_id));
+            }
+            ("GNU", NT_GNU_ABI_TAG) if desc.len() >= 16 => {
+                let os = match read_u32(desc_start)? {
+                    0 => "Linux".to_owned(),
+                    1 => "Hurd".to_owned(),
+                    2 => "Solaris".to_owned(),
+                    3 => "FreeBSD".to_owned(),
+                    os => format!("OS {os}"),
+                };
+                let major = read_u32(desc_start + 4)?;
+                let minor = read_u32(desc_start + 8)?;
+                let patch = read_u32(desc_start + 12)?;
+                notes.push((
+                    "minimum kernel".into(),
+                    format!("{os} {major}.{minor}.{patch}"),
+                ));
+            }
+            ("GNU", NT_GNU_GOLD_VERSION) => {
+                let version = String::from_utf8_lossy(desc);
+                notes.push((
+                    "gold version".into(),
+                    version.trim_end_matches('\0').to_owned(),
+                ));
+            }
+            _ => notes.push((
+                format!("{name} note type {ty}"),
+                format!("{desc_size} bytes"),
+            )),
+        }
+
+        offset = aligned_end(desc_start, desc_size)
+            .ok_or_else(|| anyhow!("Note at 0x{offset:x} has an invalid description size"))?;
+    }
+    Ok(notes)
 }
 
+/// Parses the symbols of a `SHT_SYMTAB` or `SHT_DYNSYM` section and splits them into the symbols the file exports and imports.
+/// Local symbols are only kept for `SHT_SYMTAB`, the dynamic linker has no use for the few in `SHT_DYNSYM`.
+/// A defined symbol's node spans the bytes it describes, e.g. a function's code, so it lies outside the symbol table.
+/// Undefined symbols and those without file bytes, e.g. in `.bss`, span their entry in the symbol table instead.
+/// Either way the entry is noted as the symbol's table entry.
+fn symbol_table(
+    data: &[u8],
+    section_headers: &[SectionHeader],
+    section_name_table: &[u8],
+    section_header: &SectionHeader,
+    ctx: Ctx,
+    cancel: &AtomicBool,
+) -> Result<Vec<FileNode>> {
+    let (symbols, entry_size) = parse_symbols(data, section_header, ctx)?;
+
+    // Symbol names are stored in the string table the symbol table links to, not the section name table.
+    let str_table = linked_str_table(data, section_headers, section_header)?;
+
+    let keep_locals = section_header.sh_type == SHT_SYMTAB;
+    let mut locals = vec![];
+    let mut exports = vec![];
+    let mut imports = vec![];
+    for (i, symbol) in symbols.iter().enumerate() {
+        check_cancelled(cancel)?;
+        let is_import = symbol.st_shndx == SHN_UNDEF as usize;
+        let is_local = !is_import && symbol.st_bind() == STB_LOCAL;
+        if i == 0 || (is_local && !keep_locals) {
+            // Skip the null symbol.
+            continue;
+        }
+
+        let name = match symbol.st_name {
+            0 => "unnamed symbol".to_owned(),
+            st_name => parse_str_table(str_table, st_name),
+        };
+        let defined_in_header = section_headers.get(symbol.st_shndx).filter(|_| !is_import);
+        let defined_in = if is_import {
+            "undefined".to_owned()
+        } else {
+            defined_in_header
+                .map(|header| parse_str_table(section_name_table, header.sh_name))
+                .unwrap_or_else(|| format!("section index 0x{:x}", symbol.st_shndx))
+        };
+
+        let (entry_start, entry_end) = table_entry(section_header.sh_offset, i as u64, entry_size)?;
+        let notes = vec![
+            ("value".into(), format!("0x{:x}", symbol.st_value)),
+            ("size".into(), format!("0x{:x}", symbol.st_size)),
+            ("binding".into(), bind_to_str(symbol.st_bind()).to_owned()),
+            ("type".into(), type_to_str(symbol.st_type()).to_owned()),
+            ("defined in section".into(), defined_in),
+            (
+                "table entry".into(),
+                format!("0x{entry_start:x} - 0x{entry_end:x}"),
+            ),
+        ];
+        let ((file_bytes_start, file_bytes_end), (ram_bytes_start, ram_bytes_end)) =
+            match defined_in_header.and_then(|header| {
+                let (start, end) = symbol_file_range(header, symbol.st_value, symbol.st_size)?;
+                let ram_start = start - header.sh_offset + header.sh_addr;
+                Some(((start, end), (ram_start, ram_start + (end - start))))
+            }) {
+                Some(ranges) => ranges,
+                None => (
+                    (entry_start, entry_end),
+                    table_entry(section_header.sh_addr, i as u64, entry_size)?,
+                ),
+            };
+        let node = FileNode {
+            name,
+            bytes_start: file_bytes_start,
+            bytes_end: file_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            children: vec![],
+            notes,
+            ty: SectionType::Symbol,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        if is_import {
+            imports.push(node);
+        } else if is_local {
+            locals.push(node);
+        } else {
+            exports.push(node);
+        }
+    }
+
+    Ok([
+        ("exports", exports),
+        ("imports", imports),
+        ("locals", locals),
+    ]
+    .into_iter()
+    .filter(|(_, symbols)| !symbols.is_empty())
+    .map(|(name, symbols)| FileNode::group(format!("{name} ({})", symbols.len()), symbols))
+    .collect())
+}
+
+/// The symbols of a `SHT_SYMTAB` or `SHT_DYNSYM` section and the size of each entry.
+fn parse_symbols<'a>(
+    data: &'a [u8],
+    section_header: &SectionHeader,
+    ctx: Ctx,
+) -> Result<(Symtab<'a>, u64)> {
+    let entry_size = match section_header.sh_entsize {
+        0 => goblin::elf::sym::Sym::size(ctx.container) as u64,
+        entry_size => entry_size,
+    };
+    let count = section_header.sh_size / entry_size;
+    let symbols = Symtab::parse(data, section_header.sh_offset as usize, count as usize, ctx)?;
+    Ok((symbols, entry_size))
+}
+
+/// Parses the entries of a `SHT_REL` or `SHT_RELA` section, naming each after the symbol it refers to.
+/// Relocations without a symbol, e.g. `R_X86_64_RELATIVE`, are named after their type instead.
+///
+/// A bad symbol table link doesn't fail the whole section, the affected names say what is wrong instead.
+fn relocations(
+    data: &[u8],
+    section_headers: &[SectionHeader],
+    section_header: &SectionHeader,
+    machine: u16,
+    ctx: Ctx,
+    cancel: &AtomicBool,
+) -> Result<Vec<FileNode>> {
+    let is_rela = section_header.sh_type == SHT_RELA;
+    let entry_size = match section_header.sh_entsize {
+        0 => Reloc::size(is_rela, ctx) as u64,
+        entry_size => entry_size,
+    };
+    let relocs = RelocSection::parse(
+        data,
+        section_header.sh_offset as usize,
+        section_header.sh_size as usize,
+        is_rela,
+        ctx,
+    )?;
+
+    // Relocations of the dynamic linker refer to `.dynsym`, those of object files to `.symtab`.
+    let link = section_header.sh_link as usize;
+    let symbols = match section_headers.get(link) {
+        Some(symtab) if matches!(symtab.sh_type, SHT_SYMTAB | SHT_DYNSYM) => {
+            let str_table = linked_str_table(data, section_headers, symtab);
+            parse_symbols(data, symtab, ctx)
+                .and_then(|(symbols, _)| Ok((symbols, str_table?)))
+                .map_err(|err| format!("symbol table is malformed: {err}"))
+        }
+        Some(other) => Err(format!(
+            "sh_link {link} refers to a {} section instead of a symbol table",
+            sht_to_str(other.sh_type)
+        )),
+        None => Err(format!(
+            "sh_link {link} out of bounds of the section headers"
+        )),
+    };
+    let symbol_name = |index: usize| -> String {
+        let (symbols, str_table) = match &symbols {
+            Ok(symbols) => symbols,
+            Err(err) => return err.clone(),
+        };
+        match symbols.get(index) {
+            Some(symbol) if symbol.st_name == 0 => "unnamed symbol".to_owned(),
+            Some(symbol) => parse_str_table(str_table, symbol.st_name),
+            None => format!("symbol {index} out of bounds of symbol table"),
+        }
+    };
+
+    let mut entries = vec![];
+    for (i, reloc) in relocs.iter().enumerate() {
+        check_cancelled(cancel)?;
+        let ty = format!("R_{}", r_to_str(reloc.r_type, machine));
+        let mut notes = vec![
+            ("type".into(), ty.clone()),
+            ("offset".into(), format!("0x{:x}", reloc.r_offset)),
+        ];
+        if let Some(addend) = reloc.r_addend {
+            notes.push(("addend".into(), format!("{addend:#x}")));
+        }
+        let name = if reloc.r_sym == 0 {
+            ty
+        } else {
+            let name = symbol_name(reloc.r_sym);
+            notes.push(("symbol".into(), name.clone()));
+            name
+        };
+
+        let (file_bytes_start, file_bytes_end) =
+            table_entry(section_header.sh_offset, i as u64, entry_size)?;
+        let (ram_bytes_start, ram_bytes_end) =
+            table_entry(section_header.sh_addr, i as u64, entry_size)?;
+        entries.push(FileNode {
+            name,
+            bytes_start: file_bytes_start,
+            bytes_end: file_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            children: vec![],
+            notes,
+            ty: SectionType::Relocation,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+    }
+    Ok(entries)
+}
+
+/// Where in the file the `size` bytes of a symbol at `value` defined in `section_header` are.
+/// `None` if the section takes up no space in the file or the symbol doesn't fit in it.
+///
+/// In executables `value` is an address, in object files it is an offset into the section and `sh_addr` is 0, so both work out the same.
+fn symbol_file_range(section_header: &SectionHeader, value: u64, size: u64) -> Option<(u64, u64)> {
+    if section_header.sh_type == SHT_NOBITS {
+        return None;
+    }
+    let offset = value.checked_sub(section_header.sh_addr)?;
+    if offset.checked_add(size)? > section_header.sh_size {
+        return None;
+    }
+    let start = section_header.sh_offset.checked_add(offset)?;
+    Some((start, start.checked_add(size)?))
+}
+
+/// Parses the entries of a `SHT_DYNAMIC` section up to and including the terminating `DT_NULL` entry.
+/// Entries naming a string, e.g. the libraries of `DT_NEEDED`, have it resolved from the linked string table and appended to their name.
+fn dynamic_entries(
+    data: &[u8],
+    section_headers: &[SectionHeader],
+    section_header: &SectionHeader,
+    ctx: Ctx,
+    cancel: &AtomicBool,
+) -> Result<Vec<FileNode>> {
+    let word_size = ctx.size();
+    let entry_size = match section_header.sh_entsize {
+        0 => 2 * word_size as u64,
+        entry_size => entry_size,
+    };
+    let section = section_data(data, section_header);
+    let read_word = |offset: usize| -> Result<u64> {
+        let bytes = section
+            .get(offset..offset.saturating_add(word_size))
+            .ok_or_else(|| anyhow!("Entry at 0x{offset:x} is truncated"))?;
+        let mut value = [0; 8];
+        if ctx.is_little_endian() {
+            value[..word_size].copy_from_slice(bytes);
+            Ok(u64::from_le_bytes(value))
+        } else {
+            value[8 - word_size..].copy_from_slice(bytes);
+            Ok(u64::from_be_bytes(value))
+        }
+    };
+    let str_table = linked_str_table(data, section_headers, section_header)?;
+
+    let mut entries = vec![];
+    for i in 0..section_header.sh_size / entry_size {
+        check_cancelled(cancel)?;
+        let offset = (i * entry_size) as usize;
+        let tag = read_word(offset)?;
+        let value = read_word(offset + word_size)?;
+
+        let tag_name = match tag_to_str(tag) {
+            "UNKNOWN_TAG" => format!("DT 0x{tag:x}"),
+            tag_name => tag_name.to_owned(),
+        };
+        let mut notes = vec![("value".into(), format!("0x{value:x}"))];
+        let name = match tag {
+            DT_NEEDED | DT_SONAME | DT_RPATH | DT_RUNPATH => {
+                let string = parse_str_table(str_table, value as usize);
+                let name = format!("{tag_name} {string}");
+                notes.push(("string".into(), string));
+                name
+            }
+            DT_FLAGS => {
+                let flags: Vec<String> = (0..64)
+                    .map(|bit| 1 << bit)
+                    .filter(|flag| value & flag != 0)
+                    .map(|flag| match df_tag_to_str(flag) {
+                        "UNKNOWN_TAG" => format!("0x{flag:x}"),
+                        flag => flag.to_owned(),
+                    })
+                    .collect();
+                notes.push(("flags".into(), flags.join("|")));
+                tag_name
+            }
+            _ => tag_name,
+        };
+
+        let (file_bytes_start, file_bytes_end) =
+            table_entry(section_header.sh_offset, i, entry_size)?;
+        let (ram_bytes_start, ram_bytes_end) = table_entry(section_header.sh_addr, i, entry_size)?;
+        entries.push(FileNode {
+            name,
+            bytes_start: file_bytes_start,
+            bytes_end: file_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            children: vec![],
+            notes,
+            ty: SectionType::DynamicEntry,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+        // Linkers often reserve spare entries after DT_NULL, those show up as a gap.
+        if tag == DT_NULL {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+/// A reference from one ELF section to another via its `sh_link` or `sh_info` field.
+#[derive(Debug, Clone)]
+pub struct SectionLink {
+    /// What the linked section is to the linking section, e.g. `"string table"`.
+    pub relation: &'static str,
+    /// Name of the linked section.
+    pub name: String,
+    /// Offset of the linked section in the file.
+    /// Together with `name` this identifies the linked node in both layouts, even when several sections share a name.
+    pub file_bytes_start: u64,
+}
+
+/// A region of an executable, such as a header or section, and the regions nested within it.
 #[derive(Debug, Clone)]
 pub struct FileNode {
     pub name: String,
@@ -233,6 +1618,16 @@ pub struct FileNode {
     pub ty: SectionType,
     pub notes: Vec<(String, String)>,
     pub children: Vec<FileNode>,
+    /// When set the inspector hides the children of this node.
+    pub inspector_collapsed: bool,
+    /// Other sections this section refers to, e.g. the string table of a symbol table.
+    pub links: Vec<SectionLink>,
+    /// Lazily computed CRC32 of the bytes in `file_bytes_start..file_bytes_end`.
+    content_hash: OnceLock<u32>,
+    /// See [`FileNode::occurrence`].
+    occurrence: usize,
+    /// See [`FileNode::overlaps_sibling`].
+    overlaps_sibling: bool,
 }
 
 impl FileNode {
@@ -241,25 +1636,801 @@ impl FileNode {
         self.bytes_end - self.bytes_start
     }
 
+    /// CRC32 of the section's bytes in the file, useful for checking if two sections are byte-identical.
+    /// `data` must be the data of the file this node was loaded from.
+    /// The hash is computed on first use and then cached.
+    ///
+    /// Zero initialized nodes like `.bss` own no bytes of the file, they all get the hash of no bytes.
+    pub fn content_hash(&self, data: &[u8]) -> u32 {
+        *self.content_hash.get_or_init(|| {
+            let bytes = match self.category() {
+                SectionTypeCategory::Bss => &[],
+                _ => self.file_data(data).unwrap_or(&[]),
+            };
+            crc32fast::hash(bytes)
+        })
+    }
+
+    /// The bytes in `file_bytes_start..file_bytes_end`, or `None` if they lie outside of the file.
+    /// `data` must be the data of the file this node was loaded from, see [`ExecutableFile::data`].
+    ///
+    /// Zero initialized nodes like `.bss` take up no space in the file, so their range should not be read.
+    pub fn file_data<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        data.get(self.file_bytes_start as usize..self.file_bytes_end as usize)
+    }
+
+    pub fn category(&self) -> SectionTypeCategory {
+        self.ty.category(&self.name)
+    }
+
+    /// The node reached by following `path`, a list of indexes into `children`, from this node.
+    pub fn descendant(&self, path: &[usize]) -> Option<&FileNode> {
+        path.iter().try_fold(self, |node, &i| node.children.get(i))
+    }
+
+    /// Iterates over this node and all of its descendants, depth first, parents before their children.
+    pub fn iter(&self) -> NodeIter<'_> {
+        NodeIter { stack: vec![self] }
+    }
+
+    /// Same as [`FileNode::descendant`] but mutable.
+    pub fn descendant_mut(&mut self, path: &[usize]) -> Option<&mut FileNode> {
+        path.iter()
+            .try_fold(self, |node, &i| node.children.get_mut(i))
+    }
+
+    /// Finds the first node named `name` in this tree, searching depth first.
+    ///
+    /// ELF allows several sections to share a name, use [`FileNode::find_section_occurrence`] to reach the others.
+    pub fn find_section(&self, name: &str) -> Option<&FileNode> {
+        self.find_section_occurrence(name, 0)
+    }
+
+    /// Finds the node named `name` with the given [`FileNode::occurrence`] in this tree.
+    pub fn find_section_occurrence(&self, name: &str, occurrence: usize) -> Option<&FileNode> {
+        self.iter()
+            .find(|node| node.name == name && node.occurrence == occurrence)
+    }
+
+    /// How many nodes with the same name come before this one in a depth first walk of its tree,
+    /// e.g. 1 for the second `.text` section of a file.
+    ///
+    /// Nodes of different files are matched by their name and occurrence, so the nth `.text` of one file
+    /// corresponds to the nth `.text` of another.
+    /// Siblings are sorted by offset, so the numbering follows the order the nodes appear in the file.
+    pub fn occurrence(&self) -> usize {
+        self.occurrence
+    }
+
+    /// True if the children of this node are the entries of a table, e.g. the symbols of a symbol table,
+    /// rather than sections nested within it. Defined symbols span the bytes they describe, so they lie outside their table.
+    pub fn has_entries(&self) -> bool {
+        self.children.iter().any(|child| {
+            matches!(
+                child.ty,
+                SectionType::Symbol
+                    | SectionType::Relocation
+                    | SectionType::DynamicEntry
+                    | SectionType::Group
+            )
+        })
+    }
+
+    /// True if this node shares bytes of the file with one of its siblings, see [`FileNode::overlapping_children`].
+    /// Only set in [`ExecutableFile::file_root`], once the file is loaded, so drawing doesn't need to look for overlaps.
+    pub fn overlaps_sibling(&self) -> bool {
+        self.overlaps_sibling
+    }
+
+    /// Sets [`FileNode::overlaps_sibling`] of every node in this tree.
+    fn mark_overlaps(&mut self) {
+        for (a, b) in self.overlapping_children() {
+            self.children[a].overlaps_sibling = true;
+            self.children[b].overlaps_sibling = true;
+        }
+        for child in &mut self.children {
+            child.mark_overlaps();
+        }
+    }
+
+    /// Sets [`FileNode::occurrence`] of every node in this tree.
+    fn number_occurrences(&mut self) {
+        fn number(node: &mut FileNode, seen: &mut HashMap<String, usize>) {
+            let count = seen.entry(node.name.clone()).or_default();
+            node.occurrence = *count;
+            *count += 1;
+            for child in &mut node.children {
+                number(child, seen);
+            }
+        }
+        number(self, &mut HashMap::new());
+    }
+
+    /// Creates a node spanning all of `children`.
+    fn group(name: String, children: Vec<FileNode>) -> FileNode {
+        let min = |f: fn(&FileNode) -> u64| children.iter().map(f).min().unwrap_or(0);
+        let max = |f: fn(&FileNode) -> u64| children.iter().map(f).max().unwrap_or(0);
+        FileNode {
+            name,
+            bytes_start: min(|x| x.bytes_start),
+            bytes_end: max(|x| x.bytes_end),
+            ram_bytes_start: min(|x| x.ram_bytes_start),
+            ram_bytes_end: max(|x| x.ram_bytes_end),
+            file_bytes_start: min(|x| x.file_bytes_start),
+            file_bytes_end: max(|x| x.file_bytes_end),
+            notes: vec![],
+            ty: SectionType::Group,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+            children,
+        }
+    }
+
+    /// A copy of this node and its children, laid out by their location in RAM instead of the file.
+    fn ram_layout(&self) -> FileNode {
+        FileNode {
+            bytes_start: self.ram_bytes_start,
+            bytes_end: self.ram_bytes_end,
+            children: self.children.iter().map(FileNode::ram_layout).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Moves this tree `offset` bytes further into the file, for when it was loaded from a slice of a larger file.
+    /// In the file layout the nodes themselves move too.
+    /// The targets of [`FileNode::links`] move along, so they still identify the nodes they link to.
+    fn offset_in_file(&mut self, offset: u64, file_layout: bool) {
+        self.file_bytes_start += offset;
+        self.file_bytes_end += offset;
+        for link in &mut self.links {
+            link.file_bytes_start += offset;
+        }
+        if file_layout {
+            self.bytes_start += offset;
+            self.bytes_end += offset;
+        }
+        for child in &mut self.children {
+            child.offset_in_file(offset, file_layout);
+        }
+    }
+
+    /// Orders nodes by where they start in the layout, for use with `sort_by`.
+    pub fn by_offset(a: &FileNode, b: &FileNode) -> cmp::Ordering {
+        a.bytes_start.cmp(&b.bytes_start)
+    }
+
+    /// Orders nodes from smallest to largest, for use with `sort_by`.
+    /// Nodes of equal size keep their existing order since `sort_by` is stable,
+    /// so sorting by [`FileNode::by_offset`] first ranks equal sized nodes by offset.
+    /// Use `|a, b| FileNode::by_size(b, a)` to get the largest nodes first.
+    pub fn by_size(a: &FileNode, b: &FileNode) -> cmp::Ordering {
+        a.len().cmp(&b.len())
+    }
+
     fn sort(&mut self) {
-        self.children.sort_by_key(|x| x.bytes_start);
+        self.children.sort_by(FileNode::by_offset);
         for child in &mut self.children {
             child.sort();
         }
     }
 
-    #[rustfmt::skip]
-    fn overlaps(&self, other: &Self) -> bool {
-        self.bytes_start > other.bytes_start && self.bytes_start < other.bytes_start + other.len() ||
-        self.bytes_end   > other.bytes_start && self.bytes_end   < other.bytes_start + other.len()
+    /// Adds a [`SectionType::Gap`] child for each range of this node not covered by its children, recursively.
+    /// Call after [`FileNode::sort`], and only on file layouts where every byte has a meaning.
+    /// Leaves stay leaves, and nodes with group children are skipped since groups cover their range unevenly.
+    fn fill_gaps(&mut self) {
+        for child in &mut self.children {
+            child.fill_gaps();
+        }
+        if self.children.is_empty()
+            || self
+                .children
+                .iter()
+                .any(|child| matches!(child.ty, SectionType::Group))
+        {
+            return;
+        }
+
+        let mut gaps = vec![];
+        let mut covered_to = self.bytes_start;
+        for child in &self.children {
+            if child.bytes_start > covered_to {
+                gaps.push(FileNode::gap(covered_to, child.bytes_start));
+            }
+            covered_to = covered_to.max(child.bytes_end);
+        }
+        if self.bytes_end > covered_to {
+            gaps.push(FileNode::gap(covered_to, self.bytes_end));
+        }
+        if !gaps.is_empty() {
+            self.children.extend(gaps);
+            self.children.sort_by(FileNode::by_offset);
+        }
+    }
+
+    fn gap(start: u64, end: u64) -> FileNode {
+        FileNode {
+            name: "Gap".into(),
+            bytes_start: start,
+            bytes_end: end,
+            ram_bytes_start: 0,
+            ram_bytes_end: 0,
+            file_bytes_start: start,
+            file_bytes_end: end,
+            notes: vec![],
+            children: vec![],
+            ty: SectionType::Gap,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        }
+    }
+
+    /// True if the `bytes_start..bytes_end` ranges of the nodes share at least one byte.
+    /// Touching nodes don't overlap and empty nodes never overlap.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.len() > 0
+            && other.len() > 0
+            && self.bytes_start < other.bytes_end
+            && other.bytes_start < self.bytes_end
+    }
+
+    /// The indexes of each pair of children that overlap each other, in [`FileNode::children`] order within a pair.
+    /// [`SectionType::Group`] nodes are skipped since they span unrelated nodes,
+    /// as are the entries of tables, see [`FileNode::has_entries`], since aliased symbols share their bytes.
+    pub fn overlapping_children(&self) -> Vec<(usize, usize)> {
+        if self.has_entries() {
+            return vec![];
+        }
+        let mut by_start: Vec<usize> = (0..self.children.len())
+            .filter(|&i| !matches!(self.children[i].ty, SectionType::Group))
+            .collect();
+        by_start.sort_by_key(|&i| self.children[i].bytes_start);
+
+        let mut pairs = vec![];
+        for (i, &a) in by_start.iter().enumerate() {
+            // Later children start at or after `a`, so only those starting before it ends can overlap it.
+            for &b in &by_start[i + 1..] {
+                if self.children[b].bytes_start >= self.children[a].bytes_end {
+                    break;
+                }
+                if self.children[a].overlaps(&self.children[b]) {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Iterator returned by [`FileNode::iter`].
+pub struct NodeIter<'a> {
+    /// Nodes still to visit, the next node is at the end.
+    stack: Vec<&'a FileNode>,
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = &'a FileNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// Iterator returned by [`ExecutableFile::sections_iter`] and [`ExecutableFile::sections_and_gaps_iter`].
+pub struct SectionsIter<'a> {
+    /// Nodes still to visit, the next node is at the end.
+    stack: Vec<&'a FileNode>,
+    /// True to yield [`SectionType::Gap`] nodes too.
+    gaps: bool,
+}
+
+impl<'a> Iterator for SectionsIter<'a> {
+    type Item = &'a FileNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            if !node.children.is_empty() && !node.has_entries() {
+                self.stack.extend(node.children.iter().rev());
+                continue;
+            }
+            let is_section = match node.ty {
+                SectionType::Root => false,
+                SectionType::Gap => self.gaps,
+                _ => node.category() != SectionTypeCategory::Headers,
+            };
+            if is_section {
+                return Some(node);
+            }
+        }
     }
 }
 
+/// What kind of data a [`FileNode`] contains.
 #[derive(Debug, Clone)]
 pub enum SectionType {
     ElfHeader,
     ElfSectionHeader,
     ElfProgramHeader,
+    /// A `PT_LOAD` segment described by a program header, containing the sections it loads.
+    ElfSegment {
+        p_flags: u32,
+    },
+    /// The contents of an ELF section, described by its section header.
+    ElfSection {
+        sh_type: u32,
+        sh_flags: u64,
+    },
     Text,
     Root,
+    /// An entry in a symbol table.
+    Symbol,
+    /// An entry in the `.dynamic` section, e.g. a `DT_NEEDED` library.
+    DynamicEntry,
+    /// An entry in a `SHT_REL` or `SHT_RELA` section.
+    Relocation,
+    /// Groups related nodes that are not contiguous in the file, e.g. the exported symbols of a symbol table.
+    Group,
+    /// The whole of a file loaded by [`ExecutableFile::load_raw`], its structure is unknown.
+    Raw,
+    /// The thread local storage template described by the `PT_TLS` program header, containing `.tdata` and `.tbss`.
+    TlsBlock,
+    /// The DOS, COFF and optional headers of a PE file, or one entry of its section table.
+    PeHeader,
+    /// The contents of a PE section, described by its section table entry.
+    PeSection {
+        characteristics: u32,
+    },
+    /// The Mach header or one of the load commands following it.
+    MachHeader,
+    /// A Mach-O segment such as `__TEXT`, containing its sections.
+    /// `initprot` is the memory protection the segment is mapped with.
+    MachSegment {
+        initprot: u32,
+    },
+    /// A Mach-O section such as `__text`, `initprot` is that of its segment.
+    MachSection {
+        flags: u32,
+        initprot: u32,
+    },
+    /// The Mach-O file for one architecture within a universal binary.
+    FatSlice,
+    /// The magic bytes and version at the start of a WebAssembly module.
+    WasmHeader,
+    /// A WebAssembly section, including its id and length prefix, or a segment within the data section.
+    WasmSection {
+        id: u8,
+    },
+    /// The magic bytes at the start of a static library, or the header of one of its members.
+    ArchiveHeader,
+    /// A file within a static library, usually an object file containing the nodes of its own file layout.
+    ArchiveMember,
+    /// Bytes of the parent that none of its other children describe, e.g. alignment padding.
+    Gap,
+}
+
+impl SectionType {
+    /// Classifies a node of this type named `name` into a broad category.
+    pub fn category(&self, name: &str) -> SectionTypeCategory {
+        match self {
+            SectionType::ElfHeader
+            | SectionType::ElfSectionHeader
+            | SectionType::ElfProgramHeader => SectionTypeCategory::Headers,
+            SectionType::Symbol | SectionType::Group => SectionTypeCategory::Symbols,
+            SectionType::Root
+            | SectionType::Raw
+            | SectionType::FatSlice
+            | SectionType::Gap
+            | SectionType::DynamicEntry => SectionTypeCategory::Other,
+            SectionType::Text => SectionTypeCategory::Code,
+            SectionType::TlsBlock => SectionTypeCategory::Data,
+            SectionType::Relocation => SectionTypeCategory::Relocations,
+            SectionType::ElfSegment { p_flags } => {
+                if p_flags & PF_X != 0 {
+                    SectionTypeCategory::Code
+                } else if p_flags & PF_W != 0 {
+                    SectionTypeCategory::Data
+                } else if p_flags & PF_R != 0 {
+                    SectionTypeCategory::ReadOnlyData
+                } else {
+                    SectionTypeCategory::Other
+                }
+            }
+            SectionType::PeHeader
+            | SectionType::MachHeader
+            | SectionType::WasmHeader
+            | SectionType::ArchiveHeader => SectionTypeCategory::Headers,
+            SectionType::PeSection { characteristics } => pe::category(name, *characteristics),
+            SectionType::MachSegment { initprot } => macho::segment_category(name, *initprot),
+            SectionType::MachSection { flags, initprot } => {
+                macho::section_category(name, *flags, *initprot)
+            }
+            SectionType::WasmSection { id } => wasm::category(name, *id),
+            SectionType::ArchiveMember => archive::category(name),
+            SectionType::ElfSection { sh_type, sh_flags } => {
+                if name.starts_with(".debug") || name.starts_with(".zdebug") {
+                    SectionTypeCategory::Debug
+                } else if matches!(*sh_type, SHT_SYMTAB | SHT_DYNSYM | SHT_STRTAB) {
+                    SectionTypeCategory::Symbols
+                } else if matches!(*sh_type, SHT_REL | SHT_RELA) {
+                    SectionTypeCategory::Relocations
+                } else if *sh_type == SHT_NOBITS {
+                    SectionTypeCategory::Bss
+                } else if sh_flags & SHF_EXECINSTR as u64 != 0 {
+                    SectionTypeCategory::Code
+                } else if sh_flags & SHF_WRITE as u64 != 0 {
+                    SectionTypeCategory::Data
+                } else if sh_flags & SHF_ALLOC as u64 != 0 {
+                    SectionTypeCategory::ReadOnlyData
+                } else {
+                    SectionTypeCategory::Other
+                }
+            }
+        }
+    }
+}
+
+/// Broad categories of [`SectionType`]s, for filtering and coloring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SectionTypeCategory {
+    /// File, program and section headers.
+    Headers,
+    /// Executable instructions.
+    Code,
+    /// Allocated data that is not writable.
+    ReadOnlyData,
+    /// Allocated writable data.
+    Data,
+    /// Zero initialized data that takes up no space in the file.
+    Bss,
+    /// Symbol and string tables.
+    Symbols,
+    Relocations,
+    /// Debug info, e.g. DWARF.
+    Debug,
+    Other,
+}
+
+impl SectionTypeCategory {
+    pub const ALL: [SectionTypeCategory; 9] = [
+        SectionTypeCategory::Headers,
+        SectionTypeCategory::Code,
+        SectionTypeCategory::ReadOnlyData,
+        SectionTypeCategory::Data,
+        SectionTypeCategory::Bss,
+        SectionTypeCategory::Symbols,
+        SectionTypeCategory::Relocations,
+        SectionTypeCategory::Debug,
+        SectionTypeCategory::Other,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_elf::TestElf;
+    use super::*;
+    use goblin::elf::{
+        section_header::{SHT_PROGBITS, SHT_STRTAB, SHT_SYMTAB},
+        sym::{STB_GLOBAL, STT_FUNC},
+    };
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bss_is_hashed_as_empty() {
+        let file = ExecutableFile::load_self();
+        let bss = file.ram_root.find_section(".bss").unwrap();
+        assert_eq!(bss.content_hash(&file.data), crc32fast::hash(&[]));
+    }
+
+    #[test]
+    fn loads_elf32() {
+        let mut elf = TestElf::new_32();
+        elf.section(".text", SHT_PROGBITS, vec![0x90; 0x20]);
+        elf.last().sh_flags = (SHF_ALLOC | SHF_EXECINSTR) as u64;
+        elf.last().sh_addr = 0x8000;
+        // absptr eh_frame_ptr, which is 4 bytes in a 32-bit file, then a udata4 fde_count of 5.
+        let mut eh_frame_hdr = vec![1, 0x00, 0x03, 0x3b];
+        eh_frame_hdr.extend(0x1234u32.to_le_bytes());
+        eh_frame_hdr.extend(5u32.to_le_bytes());
+        elf.section(".eh_frame_hdr", SHT_PROGBITS, eh_frame_hdr);
+        let data = elf.build();
+
+        let file = ExecutableFile::load_from_bytes("elf32".into(), &data).unwrap();
+        let text = file.file_root.find_section(".text").unwrap();
+        assert_eq!(text.len(), 0x20);
+        assert_eq!(text.file_data(&file.data), Some(&[0x90; 0x20][..]));
+        let text = file.ram_root.find_section(".text").unwrap();
+        assert_eq!((text.bytes_start, text.bytes_end), (0x8000, 0x8020));
+        assert_eq!(text.category(), SectionTypeCategory::Code);
+
+        let eh_frame_hdr = file.file_root.find_section(".eh_frame_hdr").unwrap();
+        assert!(eh_frame_hdr
+            .notes
+            .contains(&("FDEs in search table".into(), "5".into())));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn truncated_elf_is_an_error() {
+        let data = std::fs::read(current_exe().unwrap()).unwrap();
+        let header_size = std::mem::size_of::<goblin::elf64::header::Header>();
+        for len in [
+            0,
+            4,
+            16,
+            header_size - 1,
+            header_size,
+            0x1000,
+            data.len() / 2,
+        ] {
+            let result = ExecutableFile::load_from_bytes("truncated".into(), &data[..len]);
+            assert!(result.is_err(), "loaded the first 0x{len:x} bytes");
+        }
+    }
+
+    #[test]
+    fn random_bytes_are_an_error() {
+        // xorshift, so the bytes are the same every run.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut random_bytes = |len: usize| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u8
+                })
+                .collect()
+        };
+        for len in [1, 64, 0x1000] {
+            let data = random_bytes(len);
+            assert!(ExecutableFile::load_from_bytes("random".into(), &data).is_err());
+
+            // Past the magic number, so the ELF parser sees the random bytes.
+            let mut data = random_bytes(len);
+            data.splice(..4.min(len), *b"\x7fELF");
+            assert!(ExecutableFile::load_from_bytes("random".into(), &data).is_err());
+        }
+    }
+
+    #[test]
+    fn loads_more_sections_than_fit_in_the_header() {
+        // With the null section and .shstrtab, the section count and the index of .shstrtab
+        // are both past SHN_LORESERVE, so they are stored in the first section header.
+        let count = 0xff00;
+        let mut elf = TestElf::new_64();
+        for i in 0..count {
+            elf.section(&format!(".s{i}"), SHT_PROGBITS, vec![]);
+        }
+        elf.section(".last", SHT_PROGBITS, vec![0xaa; 0x10]);
+        let data = elf.build();
+        assert_eq!(
+            &data[0x3c..0x40],
+            &[0, 0, 0xff, 0xff],
+            "e_shnum and e_shstrndx"
+        );
+
+        let file = ExecutableFile::load_from_bytes("many sections".into(), &data).unwrap();
+        assert!(file
+            .file_root
+            .find_section(&format!(".s{}", count - 1))
+            .is_some());
+        let last = file.file_root.find_section(".last").unwrap();
+        assert_eq!(last.file_data(&file.data), Some(&[0xaa; 0x10][..]));
+    }
+
+    #[test]
+    fn symbol_names_come_from_the_linked_string_table() {
+        let mut elf = TestElf::new_64();
+        let text = elf.section(".text", SHT_PROGBITS, vec![0x90; 0x20]);
+        // Offsets that are also names in .shstrtab, which the symbols must not be given.
+        let strtab = elf.section(".strtab", SHT_STRTAB, b"\0main\0helper\0".to_vec());
+        let global_function = (STB_GLOBAL << 4) | STT_FUNC;
+        let local_function = (STB_LOCAL << 4) | STT_FUNC;
+        let symbols = elf.symbols(&[
+            (1, global_function, text as u16, 0, 0x10),
+            (6, local_function, text as u16, 0x10, 0x10),
+        ]);
+        elf.section(".symtab", SHT_SYMTAB, symbols);
+        elf.last().sh_link = strtab;
+        elf.last().sh_entsize = 24;
+        let file = ExecutableFile::load_from_bytes("symbols".into(), &elf.build()).unwrap();
+
+        let symtab = file.file_root.find_section(".symtab").unwrap();
+        let names: Vec<&str> = symtab
+            .iter()
+            .filter(|node| matches!(node.ty, SectionType::Symbol))
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names, ["main", "helper"]);
+    }
+
+    #[test]
+    fn defined_symbols_span_their_own_bytes() {
+        let mut elf = TestElf::new_64();
+        let text = elf.section(".text", SHT_PROGBITS, vec![0x90; 0x20]);
+        let strtab = elf.section(".strtab", SHT_STRTAB, b"\0helper\0puts\0".to_vec());
+        let function = (STB_GLOBAL << 4) | STT_FUNC;
+        let symbols = elf.symbols(&[
+            (1, function, text as u16, 0x10, 0x8),
+            (8, function, 0, 0, 0),
+        ]);
+        elf.section(".symtab", SHT_SYMTAB, symbols);
+        elf.last().sh_link = strtab;
+        elf.last().sh_entsize = 24;
+        let file = ExecutableFile::load_from_bytes("symbols".into(), &elf.build()).unwrap();
+
+        let text = file.file_root.find_section(".text").unwrap();
+        let helper = file.file_root.find_section("helper").unwrap();
+        assert_eq!(
+            (helper.bytes_start, helper.bytes_end),
+            (text.bytes_start + 0x10, text.bytes_start + 0x18)
+        );
+        assert_eq!((helper.ram_bytes_start, helper.ram_bytes_end), (0x10, 0x18));
+
+        // An import has no bytes of its own, so it spans its entry in the symbol table.
+        let symtab = file.file_root.find_section(".symtab").unwrap();
+        let puts = file.file_root.find_section("puts").unwrap();
+        assert_eq!(
+            (puts.bytes_start, puts.bytes_end),
+            (symtab.bytes_start + 48, symtab.bytes_start + 72)
+        );
+    }
+
+    #[test]
+    fn links_within_archive_members_lead_to_their_targets() {
+        let mut elf = TestElf::new_64();
+        let strtab = elf.section(".strtab", SHT_STRTAB, b"\0main\0".to_vec());
+        let symbols = elf.symbols(&[(1, (STB_GLOBAL << 4) | STT_FUNC, 0, 0, 0)]);
+        elf.section(".symtab", SHT_SYMTAB, symbols);
+        elf.last().sh_link = strtab;
+        elf.last().sh_entsize = 24;
+        let member = elf.build();
+
+        let mut archive = b"!<arch>\n".to_vec();
+        archive.extend(
+            format!(
+                "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                "test.o/",
+                0,
+                0,
+                0,
+                644,
+                member.len()
+            )
+            .as_bytes(),
+        );
+        archive.extend(&member);
+        let file = ExecutableFile::load_from_bytes("test.a".into(), &archive).unwrap();
+
+        let symtab = file.file_root.find_section(".symtab").unwrap();
+        let link = symtab
+            .links
+            .iter()
+            .find(|link| link.name == ".strtab")
+            .unwrap();
+        let target = file
+            .file_root
+            .iter()
+            .find(|node| node.name == link.name && node.file_bytes_start == link.file_bytes_start)
+            .unwrap();
+        assert_eq!(target.file_data(&file.data), Some(&b"\0main\0"[..]));
+    }
+
+    /// A node of the `start..end` bytes of the file, for testing functions that only look at ranges.
+    fn node(name: &str, start: u64, end: u64) -> FileNode {
+        FileNode {
+            name: name.to_owned(),
+            ty: SectionType::Raw,
+            ..FileNode::gap(start, end)
+        }
+    }
+
+    #[test]
+    fn iter_visits_parents_before_children() {
+        let mut root = node("root", 0, 0x40);
+        let mut first = node("first", 0, 0x20);
+        first.children = vec![node("first.a", 0, 0x10), node("first.b", 0x10, 0x20)];
+        root.children = vec![first, node("second", 0x20, 0x40)];
+        let names: Vec<&str> = root.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["root", "first", "first.a", "first.b", "second"]);
+    }
+
+    #[test]
+    fn sections_iter_yields_sections_in_file_order() {
+        let mut elf = TestElf::new_64();
+        elf.section(".text", SHT_PROGBITS, vec![0x90; 0x10]);
+        elf.section(".data", SHT_PROGBITS, vec![1; 0x10]);
+        // Padded with a gap up to the next section.
+        let strtab = elf.section(".strtab", SHT_STRTAB, b"\0main\0".to_vec());
+        let symbols = elf.symbols(&[(1, (STB_GLOBAL << 4) | STT_FUNC, 1, 0, 0x10)]);
+        elf.section(".symtab", SHT_SYMTAB, symbols);
+        elf.last().sh_link = strtab;
+        elf.last().sh_entsize = 24;
+        let file = ExecutableFile::load_from_bytes("test".into(), &elf.build()).unwrap();
+
+        let names: Vec<&str> = file
+            .sections_iter()
+            .map(|node| node.name.as_str())
+            .collect();
+        assert_eq!(names, [".text", ".data", ".strtab", ".symtab", ".shstrtab"]);
+
+        let gaps: Vec<(u64, u64)> = file
+            .sections_and_gaps_iter()
+            .filter(|node| matches!(node.ty, SectionType::Gap))
+            .map(|node| (node.file_bytes_start, node.file_bytes_end))
+            .collect();
+        let strtab = file.file_root.find_section(".strtab").unwrap();
+        assert!(gaps.contains(&(strtab.file_bytes_end, strtab.file_bytes_end + 2)));
+        assert_eq!(
+            file.sections_and_gaps_iter().count(),
+            names.len() + gaps.len()
+        );
+    }
+
+    #[test]
+    fn equal_sizes_keep_their_order() {
+        let mut nodes = [
+            node("c", 0x30, 0x40),
+            node("large", 0x00, 0x20),
+            node("a", 0x50, 0x60),
+            node("b", 0x20, 0x30),
+        ];
+        nodes.sort_by(FileNode::by_offset);
+        nodes.sort_by(|a, b| FileNode::by_size(b, a));
+        let names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["large", "b", "c", "a"]);
+
+        nodes.sort_by(FileNode::by_size);
+        let names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["b", "c", "a", "large"]);
+    }
+
+    #[test]
+    fn overlaps() {
+        let a = node("a", 0x10, 0x20);
+        // Touching
+        assert!(!a.overlaps(&node("b", 0x20, 0x30)));
+        assert!(!node("b", 0x0, 0x10).overlaps(&a));
+        // Nested
+        assert!(a.overlaps(&node("b", 0x14, 0x18)));
+        assert!(node("b", 0x14, 0x18).overlaps(&a));
+        // Identical
+        assert!(a.overlaps(&a.clone()));
+        // Partly
+        assert!(a.overlaps(&node("b", 0x1f, 0x30)));
+        // Empty
+        assert!(!a.overlaps(&node("b", 0x18, 0x18)));
+    }
+
+    #[test]
+    fn overlapping_children() {
+        let mut parent = node("parent", 0, 0x100);
+        parent.children = vec![
+            node("first", 0x00, 0x10),
+            node("touching", 0x10, 0x20),
+            node("outer", 0x40, 0x80),
+            node("identical", 0x40, 0x80),
+            node("nested", 0x50, 0x60),
+            node("after", 0x80, 0x90),
+        ];
+        assert_eq!(parent.overlapping_children(), vec![(2, 3), (2, 4), (3, 4)]);
+
+        parent.mark_overlaps();
+        let overlapping: Vec<&str> = parent
+            .children
+            .iter()
+            .filter(|child| child.overlaps_sibling())
+            .map(|child| child.name.as_str())
+            .collect();
+        assert_eq!(overlapping, ["outer", "identical", "nested"]);
+    }
 }
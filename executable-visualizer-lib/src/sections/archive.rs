@@ -0,0 +1,218 @@
+//! Loading static libraries, e.g. `.a` and `.lib` files, which are `ar` archives of object files.
+//!
+//! https://en.wikipedia.org/wiki/Ar_(Unix)
+
+use super::{
+    check_cancelled, checked_end, ExecutableFile, FileNode, SectionType, SectionTypeCategory,
+};
+use anyhow::{anyhow, Result};
+use std::sync::{atomic::AtomicBool, OnceLock};
+
+pub(super) const MAGIC: &[u8; 8] = b"!<arch>\n";
+
+/// Size of the fixed header preceding the contents of every member.
+const MEMBER_HEADER_SIZE: u64 = 60;
+
+pub(super) fn load(name: String, data: &[u8], cancel: &AtomicBool) -> Result<ExecutableFile> {
+    let mut file_children = vec![header_node("Archive Magic".into(), 0, MAGIC.len() as u64)];
+    // The GNU table of member names longer than 15 bytes.
+    let mut long_names: &[u8] = &[];
+
+    let mut offset = MAGIC.len() as u64;
+    while offset < data.len() as u64 {
+        check_cancelled(cancel)?;
+        let header_end = checked_end(offset, MEMBER_HEADER_SIZE)?;
+        let header = data
+            .get(offset as usize..header_end as usize)
+            .ok_or_else(|| anyhow!("Member header at 0x{offset:x} is truncated"))?;
+        if header[58..60] != *b"`\n" {
+            return Err(anyhow!("Member header at 0x{offset:x} has the wrong magic"));
+        }
+        let size = field(&header[48..58])
+            .parse::<u64>()
+            .map_err(|_| anyhow!("Member header at 0x{offset:x} has an invalid size"))?;
+        let end = checked_end(header_end, size)?;
+        if end > data.len() as u64 {
+            return Err(anyhow!(
+                "Member at 0x{offset:x}..0x{end:x} extends past the end of the 0x{:x} byte file.",
+                data.len()
+            ));
+        }
+
+        let raw_name = field(&header[0..16]);
+        let mut contents_start = header_end;
+        let member_name = if let Some(len) = raw_name.strip_prefix("#1/") {
+            // BSD archives store long names at the start of the contents.
+            let len = len
+                .parse::<u64>()
+                .ok()
+                .filter(|len| *len <= size)
+                .ok_or_else(|| anyhow!("Member header at 0x{offset:x} has an invalid name"))?;
+            contents_start += len;
+            let name = &data[header_end as usize..contents_start as usize];
+            String::from_utf8_lossy(name)
+                .trim_end_matches('\0')
+                .to_owned()
+        } else if let Some(name_offset) = raw_name
+            .strip_prefix('/')
+            .and_then(|name_offset| name_offset.parse::<usize>().ok())
+        {
+            let name = long_names.get(name_offset..).unwrap_or_default();
+            let name_end = name.iter().position(|c| *c == b'\n').unwrap_or(name.len());
+            String::from_utf8_lossy(&name[..name_end])
+                .trim_end_matches('/')
+                .to_owned()
+        } else if matches!(raw_name, "/" | "//" | "/SYM64/") {
+            raw_name.to_owned()
+        } else {
+            raw_name.trim_end_matches('/').to_owned()
+        };
+        let contents = &data[contents_start as usize..end as usize];
+
+        let mut notes = vec![("size".into(), format!("0x{:x}", end - contents_start))];
+        let mut children = vec![header_node(
+            "Archive Member Header".into(),
+            offset,
+            contents_start,
+        )];
+        if let Some(description) = special_member(&member_name) {
+            notes.push(("contents".into(), description.into()));
+            if member_name == "//" {
+                long_names = contents;
+            }
+        } else {
+            match ExecutableFile::load_from_bytes_cancellable(member_name.clone(), contents, cancel)
+            {
+                Ok(mut member) => {
+                    member.file_root.offset_in_file(contents_start, true);
+                    notes.extend(member.file_root.notes);
+                    children.extend(member.file_root.children);
+                }
+                Err(err) => {
+                    check_cancelled(cancel)?;
+                    notes.push(("failed to load".into(), format!("{err:#}")));
+                }
+            }
+        }
+
+        file_children.push(FileNode {
+            name: member_name,
+            bytes_start: offset,
+            bytes_end: end,
+            ram_bytes_start: 0,
+            ram_bytes_end: 0,
+            file_bytes_start: offset,
+            file_bytes_end: end,
+            notes,
+            children,
+            ty: SectionType::ArchiveMember,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+        // Members are aligned to 2 bytes.
+        offset = end + end % 2;
+    }
+
+    let mut file_root = FileNode {
+        name: "Archive".into(),
+        bytes_start: 0,
+        bytes_end: data.len() as u64,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: file_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    file_root.sort();
+    file_root.fill_gaps();
+    file_root.number_occurrences();
+
+    // The object files are only given addresses once linked, so nothing is loaded into memory.
+    let ram_root = FileNode {
+        name: "RAM".into(),
+        bytes_start: 0,
+        bytes_end: 0,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: vec![],
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+
+    Ok(ExecutableFile {
+        name,
+        file_root,
+        ram_root,
+        inspector_collapsed: false,
+        data: data.into(),
+        entry_point: None,
+        entropy: OnceLock::new(),
+    })
+}
+
+/// Classifies an archive member by its name, the object files themselves are described by their children.
+pub(super) fn category(name: &str) -> SectionTypeCategory {
+    if special_member(name).is_some() {
+        SectionTypeCategory::Symbols
+    } else {
+        SectionTypeCategory::Other
+    }
+}
+
+/// Describes the members that the archiver adds itself, rather than being files added to the archive.
+fn special_member(name: &str) -> Option<&'static str> {
+    match name {
+        "/"
+        | "/SYM64/"
+        | "__.SYMDEF"
+        | "__.SYMDEF SORTED"
+        | "__.SYMDEF_64"
+        | "__.SYMDEF_64 SORTED" => {
+            Some("symbol table mapping each global symbol to the member defining it")
+        }
+        "//" => Some("string table of member names too long for their header"),
+        _ => None,
+    }
+}
+
+/// The text of a space padded header field.
+fn field(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).unwrap_or_default().trim_end()
+}
+
+fn header_node(name: String, start: u64, end: u64) -> FileNode {
+    FileNode {
+        name,
+        bytes_start: start,
+        bytes_end: end,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: start,
+        file_bytes_end: end,
+        notes: vec![],
+        children: vec![],
+        ty: SectionType::ArchiveHeader,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    }
+}
@@ -0,0 +1,474 @@
+//! Loading Mach-O executables, as used on macOS and iOS.
+//!
+//! A Mach-O file is a header followed by load commands, some of which describe segments such as `__TEXT` and `__DATA`.
+//! Each segment is a contiguous region of the file mapped into memory and is split into sections such as `__text`.
+//! https://github.com/aidansteele/osx-abi-macho-file-format-reference
+
+use super::{
+    check_cancelled, checked_end, ExecutableFile, FileNode, SectionType, SectionTypeCategory,
+};
+use anyhow::{anyhow, Context, Result};
+use goblin::mach::{
+    constants::{
+        cputype::get_arch_name_from_types, SECTION_TYPE, S_ATTR_PURE_INSTRUCTIONS,
+        S_ATTR_SOME_INSTRUCTIONS, S_GB_ZEROFILL, S_THREAD_LOCAL_ZEROFILL, S_ZEROFILL,
+        VM_PROT_EXECUTE, VM_PROT_READ, VM_PROT_WRITE,
+    },
+    fat::{SIZEOF_FAT_ARCH, SIZEOF_FAT_HEADER},
+    header::{filetype_to_str, SIZEOF_HEADER_32, SIZEOF_HEADER_64},
+    load_command::cmd_to_str,
+    segment::Section,
+    MachO, MultiArch,
+};
+use std::sync::{atomic::AtomicBool, OnceLock};
+
+pub(super) fn load(name: String, data: &[u8], cancel: &AtomicBool) -> Result<ExecutableFile> {
+    let (mut file_root, mut ram_root) = load_roots(data, cancel)?;
+    file_root.number_occurrences();
+    ram_root.number_occurrences();
+    Ok(ExecutableFile {
+        name,
+        file_root,
+        ram_root,
+        inspector_collapsed: false,
+        data: data.into(),
+        entry_point: None,
+        entropy: OnceLock::new(),
+    })
+}
+
+/// Loads a universal binary, which contains a Mach-O file for each of several architectures.
+///
+/// Each architecture slice becomes a node named after the architecture, containing the nodes of that Mach-O file.
+/// The slices are separate programs with overlapping addresses, so in the RAM layout only the first slice is expanded.
+pub(super) fn load_fat(name: String, data: &[u8], cancel: &AtomicBool) -> Result<ExecutableFile> {
+    let multi_arch = MultiArch::new(data).context("Fat header is malformed")?;
+    let fat_header_end = (SIZEOF_FAT_HEADER + multi_arch.narches * SIZEOF_FAT_ARCH) as u64;
+    let mut fat_header = header_node("Fat Header".into(), 0, fat_header_end);
+    fat_header
+        .notes
+        .push(("architectures".into(), multi_arch.narches.to_string()));
+
+    let mut file_children = vec![fat_header];
+    let mut ram_children = vec![];
+    for (i, arch) in multi_arch.iter_arches().enumerate() {
+        check_cancelled(cancel)?;
+        let arch = arch.with_context(|| format!("Fat architecture {i} is malformed"))?;
+        let start = arch.offset as u64;
+        let end = checked_end(start, arch.size as u64)?;
+        let slice = data.get(start as usize..end as usize).ok_or_else(|| {
+            anyhow!(
+                "Architecture {i} at 0x{start:x}..0x{end:x} extends past the end of the 0x{:x} byte file.",
+                data.len()
+            )
+        })?;
+        let arch_name = get_arch_name_from_types(arch.cputype(), arch.cpusubtype())
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("cputype 0x{:x}", arch.cputype()));
+        let (mut file_root, mut ram_root) = load_roots(slice, cancel)
+            .with_context(|| format!("Failed to load the {arch_name} slice"))?;
+        file_root.offset_in_file(start, true);
+        ram_root.offset_in_file(start, false);
+
+        let mut notes = vec![
+            ("cpu type".into(), format!("0x{:x}", arch.cputype())),
+            ("cpu subtype".into(), format!("0x{:x}", arch.cpusubtype())),
+            (
+                "alignment".into(),
+                format!("0x{:x}", 1u64 << arch.align.min(63)),
+            ),
+        ];
+        // The architecture is already the name of the slice.
+        notes.extend(
+            file_root
+                .notes
+                .iter()
+                .filter(|(key, _)| key != "architecture")
+                .cloned(),
+        );
+
+        file_children.push(FileNode {
+            name: arch_name.clone(),
+            bytes_start: start,
+            bytes_end: end,
+            ram_bytes_start: 0,
+            ram_bytes_end: 0,
+            file_bytes_start: start,
+            file_bytes_end: end,
+            notes: notes.clone(),
+            children: file_root.children,
+            ty: SectionType::FatSlice,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+        let ram_bytes_start = ram_root.children.iter().map(|x| x.bytes_start).min();
+        ram_children.push(FileNode {
+            name: arch_name,
+            bytes_start: ram_bytes_start.unwrap_or(0),
+            bytes_end: ram_root.bytes_end,
+            ram_bytes_start: ram_bytes_start.unwrap_or(0),
+            ram_bytes_end: ram_root.bytes_end,
+            file_bytes_start: start,
+            file_bytes_end: end,
+            notes,
+            children: ram_root.children,
+            ty: SectionType::FatSlice,
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: i != 0,
+        });
+    }
+
+    let mut file_root = FileNode {
+        name: "Mach-O universal file".into(),
+        bytes_start: 0,
+        bytes_end: data.len() as u64,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: file_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    file_root.sort();
+    file_root.fill_gaps();
+    file_root.number_occurrences();
+
+    let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap_or(0);
+    let mut ram_root = FileNode {
+        name: "RAM".into(),
+        bytes_start: 0,
+        bytes_end: ram_bytes_end,
+        ram_bytes_start: 0,
+        ram_bytes_end,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: ram_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    ram_root.number_occurrences();
+
+    Ok(ExecutableFile {
+        name,
+        file_root,
+        ram_root,
+        inspector_collapsed: false,
+        data: data.into(),
+        entry_point: None,
+        entropy: OnceLock::new(),
+    })
+}
+
+/// The sorted file and RAM trees of the Mach-O file `data`.
+fn load_roots(data: &[u8], cancel: &AtomicBool) -> Result<(FileNode, FileNode)> {
+    let macho = MachO::parse(data, 0).context("Mach-O headers are malformed")?;
+    let header_size = if macho.is_64 {
+        SIZEOF_HEADER_64
+    } else {
+        SIZEOF_HEADER_32
+    };
+
+    let mut headers = vec![header_node("Mach Header".into(), 0, header_size as u64)];
+    for (i, load_command) in macho.load_commands.iter().enumerate() {
+        let start = load_command.offset as u64;
+        let end = checked_end(start, load_command.command.cmdsize() as u64)
+            .with_context(|| format!("Load command {i} is malformed"))?;
+        headers.push(header_node(
+            format!(
+                "Load Command #{i} {}",
+                cmd_to_str(load_command.command.cmd())
+            ),
+            start,
+            end,
+        ));
+    }
+
+    let mut file_children = vec![];
+    let mut ram_children = vec![];
+    for segment in macho.segments.iter() {
+        check_cancelled(cancel)?;
+        // Object files put all of their sections in a single segment without a name.
+        let segment_name = match segment.name() {
+            Ok("") => "(unnamed segment)".to_owned(),
+            Ok(name) => name.to_owned(),
+            Err(_) => "invalid segment name".to_owned(),
+        };
+        let sections = segment
+            .sections()
+            .with_context(|| format!("Sections of segment {segment_name} are malformed"))?;
+
+        let mut file_sections = vec![];
+        let mut ram_sections = vec![];
+        for (section, _) in &sections {
+            let section_name = section.name().unwrap_or("invalid section name").to_owned();
+            let ram_bytes_start = section.addr;
+            let ram_bytes_end = checked_end(section.addr, section.size)
+                .with_context(|| format!("Address range of section {section_name} is malformed"))?;
+            let zero_fill = matches!(
+                section.flags & SECTION_TYPE,
+                S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL
+            );
+            let (file_bytes_start, file_bytes_end) = if zero_fill {
+                (0, 0)
+            } else {
+                let start = section.offset as u64;
+                (start, checked_end(start, section.size)?)
+            };
+            check_in_file(&section_name, file_bytes_end, data)?;
+            let node = FileNode {
+                name: section_name,
+                bytes_start: ram_bytes_start,
+                bytes_end: ram_bytes_end,
+                ram_bytes_start,
+                ram_bytes_end,
+                file_bytes_start,
+                file_bytes_end,
+                notes: section_notes(section),
+                children: vec![],
+                ty: SectionType::MachSection {
+                    flags: section.flags,
+                    initprot: segment.initprot,
+                },
+                links: vec![],
+                content_hash: OnceLock::new(),
+                occurrence: 0,
+                overlaps_sibling: false,
+                inspector_collapsed: false,
+            };
+            // Zero filled sections take up no space in the file.
+            if !zero_fill && section.size != 0 {
+                file_sections.push(FileNode {
+                    bytes_start: file_bytes_start,
+                    bytes_end: file_bytes_end,
+                    ..node.clone()
+                });
+            }
+            ram_sections.push(node);
+        }
+
+        let ram_bytes_start = segment.vmaddr;
+        let ram_bytes_end = checked_end(segment.vmaddr, segment.vmsize)
+            .with_context(|| format!("Address range of segment {segment_name} is malformed"))?;
+        let file_bytes_start = segment.fileoff;
+        let file_bytes_end = checked_end(segment.fileoff, segment.filesize)
+            .with_context(|| format!("File range of segment {segment_name} is malformed"))?;
+        check_in_file(&segment_name, file_bytes_end, data)?;
+        let segment_node = FileNode {
+            name: segment_name,
+            bytes_start: ram_bytes_start,
+            bytes_end: ram_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            notes: segment_notes(segment.vmaddr, segment.initprot, segment.maxprot),
+            children: ram_sections,
+            ty: SectionType::MachSegment {
+                initprot: segment.initprot,
+            },
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        // `__PAGEZERO` is only reserved address space and `__LINKEDIT` is only in the file.
+        if segment.filesize != 0 {
+            file_children.push(FileNode {
+                bytes_start: file_bytes_start,
+                bytes_end: file_bytes_end,
+                children: file_sections,
+                ..segment_node.clone()
+            });
+        }
+        if segment.vmsize != 0 {
+            ram_children.push(segment_node);
+        }
+    }
+
+    // The headers are usually at the start of the `__TEXT` segment, so they belong inside it.
+    for header in headers {
+        let segment = file_children.iter_mut().find(|segment| {
+            segment.file_bytes_start <= header.file_bytes_start
+                && header.file_bytes_end <= segment.file_bytes_end
+        });
+        match segment {
+            Some(segment) => segment.children.push(header),
+            None => file_children.push(header),
+        }
+    }
+
+    let header = &macho.header;
+    let root_notes = vec![
+        (
+            "architecture".into(),
+            get_arch_name_from_types(header.cputype(), header.cpusubtype())
+                .unwrap_or("unknown")
+                .to_owned(),
+        ),
+        (
+            "file type".into(),
+            filetype_to_str(header.filetype).to_owned(),
+        ),
+    ];
+
+    let mut file_root = FileNode {
+        name: "Mach-O file".into(),
+        bytes_start: 0,
+        bytes_end: data.len() as u64,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: root_notes.clone(),
+        children: file_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    file_root.sort();
+    file_root.fill_gaps();
+
+    let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap_or(0);
+    let mut ram_root = FileNode {
+        name: "RAM".into(),
+        bytes_start: 0,
+        bytes_end: ram_bytes_end,
+        ram_bytes_start: 0,
+        ram_bytes_end,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: root_notes,
+        children: ram_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    ram_root.sort();
+
+    Ok((file_root, ram_root))
+}
+
+/// Classifies a segment by its name and initial memory protection.
+pub(super) fn segment_category(name: &str, initprot: u32) -> SectionTypeCategory {
+    if name == "__LINKEDIT" {
+        // Symbols, strings and the information used by the dynamic linker.
+        SectionTypeCategory::Symbols
+    } else if name == "__DWARF" {
+        SectionTypeCategory::Debug
+    } else if initprot & VM_PROT_EXECUTE != 0 {
+        SectionTypeCategory::Code
+    } else if initprot & VM_PROT_WRITE != 0 {
+        SectionTypeCategory::Data
+    } else if initprot & VM_PROT_READ != 0 {
+        SectionTypeCategory::ReadOnlyData
+    } else {
+        SectionTypeCategory::Other
+    }
+}
+
+/// Classifies a section by its name, its `flags` and the initial memory protection of its segment.
+pub(super) fn section_category(name: &str, flags: u32, initprot: u32) -> SectionTypeCategory {
+    if name.starts_with("__debug") || name.starts_with("__apple_") {
+        SectionTypeCategory::Debug
+    } else if matches!(
+        flags & SECTION_TYPE,
+        S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL
+    ) {
+        SectionTypeCategory::Bss
+    } else if flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0 {
+        SectionTypeCategory::Code
+    } else if initprot & VM_PROT_WRITE != 0 {
+        SectionTypeCategory::Data
+    } else {
+        SectionTypeCategory::ReadOnlyData
+    }
+}
+
+fn check_in_file(name: &str, file_bytes_end: u64, data: &[u8]) -> Result<()> {
+    if file_bytes_end > data.len() as u64 {
+        return Err(anyhow!(
+            "{name} ends at 0x{file_bytes_end:x}, past the end of the 0x{:x} byte file.",
+            data.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Memory protection flags in the style of `ls`, e.g. `r-x`.
+fn protection_text(protection: u32) -> String {
+    [
+        (VM_PROT_READ, 'r'),
+        (VM_PROT_WRITE, 'w'),
+        (VM_PROT_EXECUTE, 'x'),
+    ]
+    .iter()
+    .map(|(flag, c)| if protection & flag != 0 { *c } else { '-' })
+    .collect()
+}
+
+fn segment_notes(vmaddr: u64, initprot: u32, maxprot: u32) -> Vec<(String, String)> {
+    vec![
+        ("address".into(), format!("0x{vmaddr:x}")),
+        ("initial protection".into(), protection_text(initprot)),
+        ("maximum protection".into(), protection_text(maxprot)),
+    ]
+}
+
+fn section_notes(section: &Section) -> Vec<(String, String)> {
+    let mut notes = vec![
+        ("flags".into(), format!("0x{:x}", section.flags)),
+        (
+            "address alignment".into(),
+            format!("0x{:x}", 1u64 << section.align.min(63)),
+        ),
+    ];
+    if section.nreloc != 0 {
+        notes.push(("relocations".into(), section.nreloc.to_string()));
+    }
+    notes
+}
+
+/// A node for the Mach header, a load command or the fat header at `start..end` in the file.
+fn header_node(name: String, start: u64, end: u64) -> FileNode {
+    FileNode {
+        name,
+        bytes_start: start,
+        bytes_end: end,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: start,
+        file_bytes_end: end,
+        notes: vec![],
+        children: vec![],
+        ty: SectionType::MachHeader,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    }
+}
@@ -0,0 +1,303 @@
+//! Loading Windows PE/COFF executables, e.g. `.exe` and `.dll` files.
+//!
+//! https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
+
+use super::{
+    check_cancelled, checked_end, table_entry, ExecutableFile, FileNode, SectionType,
+    SectionTypeCategory,
+};
+use anyhow::{anyhow, Context, Result};
+use goblin::pe::{
+    header::{machine_to_str, Header, SIZEOF_COFF_HEADER, SIZEOF_PE_MAGIC},
+    section_table::{
+        SectionTable, IMAGE_SCN_ALIGN_MASK, IMAGE_SCN_CNT_CODE, IMAGE_SCN_CNT_INITIALIZED_DATA,
+        IMAGE_SCN_CNT_UNINITIALIZED_DATA, IMAGE_SCN_LNK_COMDAT, IMAGE_SCN_LNK_INFO,
+        IMAGE_SCN_LNK_REMOVE, IMAGE_SCN_MEM_DISCARDABLE, IMAGE_SCN_MEM_EXECUTE,
+        IMAGE_SCN_MEM_NOT_CACHED, IMAGE_SCN_MEM_NOT_PAGED, IMAGE_SCN_MEM_READ,
+        IMAGE_SCN_MEM_SHARED, IMAGE_SCN_MEM_WRITE, SIZEOF_SECTION_TABLE,
+    },
+};
+use std::sync::{atomic::AtomicBool, OnceLock};
+
+/// Size of the MS-DOS header at the start of every PE file.
+const DOS_HEADER_SIZE: u64 = 0x40;
+
+const CHARACTERISTICS: [(u32, &str); 13] = [
+    (IMAGE_SCN_CNT_CODE, "CNT_CODE"),
+    (IMAGE_SCN_CNT_INITIALIZED_DATA, "CNT_INITIALIZED_DATA"),
+    (IMAGE_SCN_CNT_UNINITIALIZED_DATA, "CNT_UNINITIALIZED_DATA"),
+    (IMAGE_SCN_LNK_INFO, "LNK_INFO"),
+    (IMAGE_SCN_LNK_REMOVE, "LNK_REMOVE"),
+    (IMAGE_SCN_LNK_COMDAT, "LNK_COMDAT"),
+    (IMAGE_SCN_MEM_DISCARDABLE, "MEM_DISCARDABLE"),
+    (IMAGE_SCN_MEM_NOT_CACHED, "MEM_NOT_CACHED"),
+    (IMAGE_SCN_MEM_NOT_PAGED, "MEM_NOT_PAGED"),
+    (IMAGE_SCN_MEM_SHARED, "MEM_SHARED"),
+    (IMAGE_SCN_MEM_EXECUTE, "MEM_EXECUTE"),
+    (IMAGE_SCN_MEM_READ, "MEM_READ"),
+    (IMAGE_SCN_MEM_WRITE, "MEM_WRITE"),
+];
+
+pub(super) fn load(name: String, data: &[u8], cancel: &AtomicBool) -> Result<ExecutableFile> {
+    let header = Header::parse(data).context("PE headers are malformed")?;
+    let pe_header_start = header.dos_header.pe_pointer as u64;
+    let optional_header_start = pe_header_start + (SIZEOF_PE_MAGIC + SIZEOF_COFF_HEADER) as u64;
+    let section_table_start =
+        optional_header_start + header.coff_header.size_of_optional_header as u64;
+
+    let mut file_children = vec![
+        header_node("DOS Header", 0, DOS_HEADER_SIZE),
+        header_node("PE Header", pe_header_start, optional_header_start),
+    ];
+    // Some linkers don't leave room for a DOS stub.
+    if pe_header_start > DOS_HEADER_SIZE {
+        file_children.push(header_node("DOS Stub", DOS_HEADER_SIZE, pe_header_start));
+    }
+    if header.optional_header.is_some() {
+        file_children.push(header_node(
+            "Optional Header",
+            optional_header_start,
+            section_table_start,
+        ));
+    }
+
+    let mut offset = section_table_start as usize;
+    let sections = header
+        .coff_header
+        .sections(data, &mut offset)
+        .context("PE section table is malformed")?;
+
+    let mut ram_children = vec![];
+    // Object files have no optional header, and so aren't mapped into memory as a whole.
+    if let Some(optional_header) = &header.optional_header {
+        let size_of_headers = optional_header.windows_fields.size_of_headers as u64;
+        let mut headers = header_node("Headers", 0, size_of_headers);
+        headers.ram_bytes_end = size_of_headers;
+        ram_children.push(headers);
+    }
+
+    for (i, section) in sections.iter().enumerate() {
+        check_cancelled(cancel)?;
+        let section_name = section_name(section);
+        let (bytes_start, bytes_end) =
+            table_entry(section_table_start, i as u64, SIZEOF_SECTION_TABLE as u64)
+                .with_context(|| format!("Section table entry {i} is malformed"))?;
+        file_children.push(header_node(
+            &format!("PE Section Header for {section_name}"),
+            bytes_start,
+            bytes_end,
+        ));
+
+        let file_bytes_start = section.pointer_to_raw_data as u64;
+        let file_bytes_end = checked_end(file_bytes_start, section.size_of_raw_data as u64)
+            .with_context(|| format!("File range of section {section_name} is malformed"))?;
+        if file_bytes_end > data.len() as u64 {
+            return Err(anyhow!(
+                "Section {section_name} at 0x{file_bytes_start:x}..0x{file_bytes_end:x} extends past the end of the 0x{:x} byte file.",
+                data.len()
+            ));
+        }
+        // Object files leave the virtual size as 0.
+        let ram_len = if section.virtual_size == 0 {
+            section.size_of_raw_data
+        } else {
+            section.virtual_size
+        };
+        let ram_bytes_start = section.virtual_address as u64;
+        let ram_bytes_end = checked_end(ram_bytes_start, ram_len as u64)
+            .with_context(|| format!("Address range of section {section_name} is malformed"))?;
+        let ty = SectionType::PeSection {
+            characteristics: section.characteristics,
+        };
+        let notes = section_notes(section);
+
+        if header.optional_header.is_some() {
+            ram_children.push(FileNode {
+                name: section_name.clone(),
+                bytes_start: ram_bytes_start,
+                bytes_end: ram_bytes_end,
+                ram_bytes_start,
+                ram_bytes_end,
+                file_bytes_start,
+                file_bytes_end,
+                ty: ty.clone(),
+                notes: notes.clone(),
+                children: vec![],
+                links: vec![],
+                content_hash: OnceLock::new(),
+                occurrence: 0,
+                overlaps_sibling: false,
+                inspector_collapsed: false,
+            });
+        }
+        // Uninitialized data such as `.bss` takes up no space in the file.
+        if section.size_of_raw_data != 0 {
+            file_children.push(FileNode {
+                name: section_name,
+                bytes_start: file_bytes_start,
+                bytes_end: file_bytes_end,
+                ram_bytes_start,
+                ram_bytes_end,
+                file_bytes_start,
+                file_bytes_end,
+                ty,
+                notes,
+                children: vec![],
+                links: vec![],
+                content_hash: OnceLock::new(),
+                occurrence: 0,
+                overlaps_sibling: false,
+                inspector_collapsed: false,
+            });
+        }
+    }
+
+    let mut root_notes = vec![(
+        "machine".into(),
+        machine_to_str(header.coff_header.machine).to_owned(),
+    )];
+    if let Some(optional_header) = &header.optional_header {
+        root_notes.push((
+            "image base".into(),
+            format!("0x{:x}", optional_header.windows_fields.image_base),
+        ));
+    }
+
+    let mut file_root = FileNode {
+        name: "PE file".into(),
+        bytes_start: 0,
+        bytes_end: data.len() as u64,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: root_notes.clone(),
+        children: file_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    file_root.sort();
+    file_root.fill_gaps();
+    file_root.number_occurrences();
+
+    // Addresses are relative to the image base, which is where the headers are mapped.
+    let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap_or(0);
+    let mut ram_root = FileNode {
+        name: "RAM".into(),
+        bytes_start: 0,
+        bytes_end: ram_bytes_end,
+        ram_bytes_start: 0,
+        ram_bytes_end,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: root_notes,
+        children: ram_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    ram_root.sort();
+    ram_root.number_occurrences();
+
+    Ok(ExecutableFile {
+        name,
+        file_root,
+        ram_root,
+        inspector_collapsed: false,
+        data: data.into(),
+        entry_point: None,
+        entropy: OnceLock::new(),
+    })
+}
+
+/// Classifies a PE section by its name and `characteristics` flags.
+pub(super) fn category(name: &str, characteristics: u32) -> SectionTypeCategory {
+    let has = |flag: u32| characteristics & flag != 0;
+    if name.starts_with(".debug") {
+        SectionTypeCategory::Debug
+    } else if name == ".reloc" {
+        SectionTypeCategory::Relocations
+    } else if has(IMAGE_SCN_CNT_UNINITIALIZED_DATA) && !has(IMAGE_SCN_CNT_INITIALIZED_DATA) {
+        SectionTypeCategory::Bss
+    } else if has(IMAGE_SCN_CNT_CODE) || has(IMAGE_SCN_MEM_EXECUTE) {
+        SectionTypeCategory::Code
+    } else if has(IMAGE_SCN_MEM_WRITE) {
+        SectionTypeCategory::Data
+    } else {
+        SectionTypeCategory::ReadOnlyData
+    }
+}
+
+/// The full name of a section, looking up long names like `/4` in the COFF string table.
+fn section_name(section: &SectionTable) -> String {
+    match &section.real_name {
+        Some(name) => name.clone(),
+        None => section
+            .name()
+            .map(str::to_owned)
+            .unwrap_or_else(|_| "invalid section name".to_owned()),
+    }
+}
+
+fn section_notes(section: &SectionTable) -> Vec<(String, String)> {
+    let mut flags = String::new();
+    for (flag, flag_name) in CHARACTERISTICS {
+        if section.characteristics & flag != 0 {
+            if !flags.is_empty() {
+                flags.push('|')
+            }
+            flags.push_str(flag_name);
+        }
+    }
+    if flags.is_empty() {
+        flags = "NONE".to_owned();
+    }
+    let mut notes = vec![
+        ("characteristics".into(), flags),
+        (
+            "virtual size".into(),
+            format!("0x{:x}", section.virtual_size),
+        ),
+        (
+            "raw size".into(),
+            format!("0x{:x}", section.size_of_raw_data),
+        ),
+    ];
+    // The alignment is stored as log2 + 1, and is only set in object files.
+    let align = (section.characteristics & IMAGE_SCN_ALIGN_MASK) >> 20;
+    if align != 0 {
+        notes.push((
+            "address alignment".into(),
+            format!("0x{:x}", 1 << (align - 1)),
+        ));
+    }
+    notes
+}
+
+/// A node for a region of the headers at `start..end` in the file.
+fn header_node(name: &str, start: u64, end: u64) -> FileNode {
+    FileNode {
+        name: name.to_owned(),
+        bytes_start: start,
+        bytes_end: end,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: start,
+        file_bytes_end: end,
+        notes: vec![],
+        children: vec![],
+        ty: SectionType::PeHeader,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    }
+}
@@ -0,0 +1,220 @@
+//! Builds small ELF files for tests, so they don't depend on the executables installed on the machine.
+
+use goblin::elf::section_header::{SHN_XINDEX, SHT_STRTAB};
+
+/// Section numbers from here on don't fit in the ELF header and are stored in the first section header instead.
+const SHN_LORESERVE: usize = 0xff00;
+
+/// A section of a [`TestElf`], its contents are placed in the file in the order the sections were added.
+pub(crate) struct TestSection {
+    pub name: String,
+    pub sh_type: u32,
+    pub sh_flags: u64,
+    pub sh_addr: u64,
+    pub sh_link: u32,
+    pub sh_entsize: u64,
+    pub contents: Vec<u8>,
+}
+
+/// An ELF file without program headers, made of a header, the contents of its sections,
+/// a `.shstrtab` and the section headers, in that order.
+pub(crate) struct TestElf {
+    pub is_64: bool,
+    pub is_little_endian: bool,
+    pub sections: Vec<TestSection>,
+}
+
+impl TestElf {
+    pub fn new_64() -> Self {
+        TestElf {
+            is_64: true,
+            is_little_endian: true,
+            sections: vec![],
+        }
+    }
+
+    pub fn new_32() -> Self {
+        TestElf {
+            is_64: false,
+            ..Self::new_64()
+        }
+    }
+
+    /// Adds a section and returns its index in the section headers, the null section header is index 0.
+    pub fn section(&mut self, name: &str, sh_type: u32, contents: Vec<u8>) -> u32 {
+        self.sections.push(TestSection {
+            name: name.to_owned(),
+            sh_type,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_link: 0,
+            sh_entsize: 0,
+            contents,
+        });
+        self.sections.len() as u32
+    }
+
+    /// The last added section, for setting the fields [`TestElf::section`] leaves at 0.
+    pub fn last(&mut self) -> &mut TestSection {
+        self.sections.last_mut().unwrap()
+    }
+
+    /// The entries of a symbol table, each a name offset, `st_info`, section index, value and size.
+    /// The null symbol is added in front.
+    pub fn symbols(&self, symbols: &[(u32, u8, u16, u64, u64)]) -> Vec<u8> {
+        let mut out = vec![];
+        for (st_name, st_info, st_shndx, st_value, st_size) in
+            [(0, 0, 0, 0, 0)].iter().chain(symbols).copied()
+        {
+            self.put(&mut out, st_name as u64, 4);
+            if self.is_64 {
+                out.extend([st_info, 0]);
+                self.put(&mut out, st_shndx as u64, 2);
+                self.put(&mut out, st_value, 8);
+                self.put(&mut out, st_size, 8);
+            } else {
+                self.put(&mut out, st_value, 4);
+                self.put(&mut out, st_size, 4);
+                out.extend([st_info, 0]);
+                self.put(&mut out, st_shndx as u64, 2);
+            }
+        }
+        out
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let word = if self.is_64 { 8 } else { 4 };
+        let header_size = if self.is_64 { 64 } else { 52 };
+        let section_header_size = if self.is_64 { 64 } else { 40 };
+
+        let mut shstrtab = vec![0];
+        let mut name_offset = |name: &str| {
+            let offset = shstrtab.len() as u64;
+            shstrtab.extend(name.as_bytes());
+            shstrtab.push(0);
+            offset
+        };
+        let names: Vec<u64> = self
+            .sections
+            .iter()
+            .map(|section| name_offset(&section.name))
+            .collect();
+        let shstrtab_name = name_offset(".shstrtab");
+
+        let mut data = vec![0; header_size];
+        let mut offsets = vec![];
+        for section in &self.sections {
+            align(&mut data, 8);
+            offsets.push(data.len() as u64);
+            data.extend(&section.contents);
+        }
+        let shstrtab_offset = data.len() as u64;
+        data.extend(&shstrtab);
+        align(&mut data, 8);
+        let shoff = data.len() as u64;
+
+        // The null section header, the shstrtab and the added sections.
+        let count = self.sections.len() + 2;
+        let shstrndx = count - 1;
+        let mut null = [0u64; 2];
+        if count >= SHN_LORESERVE {
+            null[0] = count as u64;
+        }
+        if shstrndx >= SHN_LORESERVE {
+            null[1] = shstrndx as u64;
+        }
+        self.section_header(&mut data, 0, 0, 0, 0, 0, null[0], null[1] as u32, 0);
+        for ((section, name), offset) in self.sections.iter().zip(names).zip(offsets) {
+            self.section_header(
+                &mut data,
+                name,
+                section.sh_type,
+                section.sh_flags,
+                section.sh_addr,
+                offset,
+                section.contents.len() as u64,
+                section.sh_link,
+                section.sh_entsize,
+            );
+        }
+        let shstrtab_len = shstrtab.len() as u64;
+        self.section_header(
+            &mut data,
+            shstrtab_name,
+            SHT_STRTAB,
+            0,
+            0,
+            shstrtab_offset,
+            shstrtab_len,
+            0,
+            0,
+        );
+
+        let mut header = vec![0x7f, b'E', b'L', b'F'];
+        header.push(if self.is_64 { 2 } else { 1 });
+        header.push(if self.is_little_endian { 1 } else { 2 });
+        header.push(1);
+        header.resize(16, 0);
+        self.put(&mut header, 1, 2); // e_type, ET_REL
+        self.put(&mut header, if self.is_64 { 62 } else { 3 }, 2); // e_machine, x86-64 or i386
+        self.put(&mut header, 1, 4); // e_version
+        self.put(&mut header, 0, word); // e_entry
+        self.put(&mut header, 0, word); // e_phoff
+        self.put(&mut header, shoff, word);
+        self.put(&mut header, 0, 4); // e_flags
+        self.put(&mut header, header_size as u64, 2);
+        self.put(&mut header, 0, 2); // e_phentsize
+        self.put(&mut header, 0, 2); // e_phnum
+        self.put(&mut header, section_header_size, 2);
+        let e_shnum = if count >= SHN_LORESERVE { 0 } else { count };
+        self.put(&mut header, e_shnum as u64, 2);
+        let e_shstrndx = if shstrndx >= SHN_LORESERVE {
+            SHN_XINDEX as usize
+        } else {
+            shstrndx
+        };
+        self.put(&mut header, e_shstrndx as u64, 2);
+        assert_eq!(header.len(), header_size);
+        data[..header_size].copy_from_slice(&header);
+        data
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn section_header(
+        &self,
+        out: &mut Vec<u8>,
+        sh_name: u64,
+        sh_type: u32,
+        sh_flags: u64,
+        sh_addr: u64,
+        sh_offset: u64,
+        sh_size: u64,
+        sh_link: u32,
+        sh_entsize: u64,
+    ) {
+        let word = if self.is_64 { 8 } else { 4 };
+        self.put(out, sh_name, 4);
+        self.put(out, sh_type as u64, 4);
+        self.put(out, sh_flags, word);
+        self.put(out, sh_addr, word);
+        self.put(out, sh_offset, word);
+        self.put(out, sh_size, word);
+        self.put(out, sh_link as u64, 4);
+        self.put(out, 0, 4); // sh_info
+        self.put(out, 1, word); // sh_addralign
+        self.put(out, sh_entsize, word);
+    }
+
+    /// Appends the low `size` bytes of `value` in the byte order of the file.
+    fn put(&self, out: &mut Vec<u8>, value: u64, size: usize) {
+        if self.is_little_endian {
+            out.extend(&value.to_le_bytes()[..size]);
+        } else {
+            out.extend(&value.to_be_bytes()[8 - size..]);
+        }
+    }
+}
+
+fn align(data: &mut Vec<u8>, alignment: usize) {
+    data.resize(data.len().next_multiple_of(alignment), 0);
+}
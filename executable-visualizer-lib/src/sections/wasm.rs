@@ -0,0 +1,336 @@
+//! Loading WebAssembly modules, e.g. `.wasm` files.
+//!
+//! https://webassembly.github.io/spec/core/binary/modules.html
+
+use super::{
+    check_cancelled, checked_end, ExecutableFile, FileNode, SectionType, SectionTypeCategory,
+};
+use anyhow::{anyhow, Context, Result};
+use std::sync::{atomic::AtomicBool, OnceLock};
+
+/// Size of the magic bytes and version at the start of every module.
+const PREAMBLE_SIZE: u64 = 8;
+
+const CUSTOM: u8 = 0;
+const IMPORT: u8 = 2;
+const EXPORT: u8 = 7;
+const START: u8 = 8;
+const CODE: u8 = 10;
+const DATA: u8 = 11;
+const DATA_COUNT: u8 = 12;
+
+/// The `i32.const` and `end` instructions that make up the offset expression of an active data segment.
+const I32_CONST: u8 = 0x41;
+const END: u8 = 0x0b;
+
+pub(super) fn load(name: String, data: &[u8], cancel: &AtomicBool) -> Result<ExecutableFile> {
+    let mut file_children = vec![FileNode {
+        name: "WASM Header".into(),
+        bytes_start: 0,
+        bytes_end: PREAMBLE_SIZE,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: PREAMBLE_SIZE,
+        notes: vec![],
+        children: vec![],
+        ty: SectionType::WasmHeader,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    }];
+    let mut ram_children = vec![];
+
+    let mut reader = Reader {
+        data,
+        offset: PREAMBLE_SIZE as usize,
+    };
+    while !reader.is_empty() {
+        check_cancelled(cancel)?;
+        let bytes_start = reader.offset as u64;
+        let id = reader.u8()?;
+        let len = reader.uleb()?;
+        let payload_start = reader.offset as u64;
+        let payload = reader
+            .bytes(len)
+            .with_context(|| format!("Section at 0x{bytes_start:x} is malformed"))?;
+        let bytes_end = reader.offset as u64;
+
+        let mut payload = Reader {
+            data: payload,
+            offset: 0,
+        };
+        let mut notes = vec![];
+        let section_name = if id == CUSTOM {
+            let custom_name = payload
+                .name()
+                .with_context(|| format!("Custom section at 0x{bytes_start:x} is malformed"))?;
+            notes.push(("name".into(), custom_name.clone()));
+            format!("Custom Section {custom_name}")
+        } else {
+            let section_name = section_name(id)
+                .ok_or_else(|| anyhow!("Section at 0x{bytes_start:x} has unknown id {id}"))?;
+            if !matches!(id, START | DATA_COUNT) {
+                notes.push(("entries".into(), payload.uleb()?.to_string()));
+            }
+            section_name.to_owned()
+        };
+
+        let mut children = vec![];
+        if id == DATA {
+            // Bail out of describing the segments rather than the whole module, the section itself is still valid.
+            if let Err(err) = data_segments(
+                &mut payload,
+                payload_start,
+                &mut children,
+                &mut ram_children,
+            ) {
+                notes.push(("segments".into(), format!("{err:#}")));
+            }
+        }
+
+        file_children.push(FileNode {
+            name: section_name,
+            bytes_start,
+            bytes_end,
+            ram_bytes_start: 0,
+            ram_bytes_end: 0,
+            file_bytes_start: bytes_start,
+            file_bytes_end: bytes_end,
+            notes,
+            children,
+            ty: SectionType::WasmSection { id },
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        });
+    }
+
+    let mut file_root = FileNode {
+        name: "WASM module".into(),
+        bytes_start: 0,
+        bytes_end: data.len() as u64,
+        ram_bytes_start: 0,
+        ram_bytes_end: 0,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: file_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    file_root.sort();
+    file_root.fill_gaps();
+    file_root.number_occurrences();
+
+    // Only active data segments have a fixed place in linear memory.
+    let ram_bytes_end = ram_children.iter().map(|x| x.bytes_end).max().unwrap_or(0);
+    let mut ram_root = FileNode {
+        name: "Linear Memory".into(),
+        bytes_start: 0,
+        bytes_end: ram_bytes_end,
+        ram_bytes_start: 0,
+        ram_bytes_end,
+        file_bytes_start: 0,
+        file_bytes_end: data.len() as u64,
+        notes: vec![],
+        children: ram_children,
+        ty: SectionType::Root,
+        links: vec![],
+        content_hash: OnceLock::new(),
+        occurrence: 0,
+        overlaps_sibling: false,
+        inspector_collapsed: false,
+    };
+    ram_root.sort();
+    ram_root.number_occurrences();
+
+    Ok(ExecutableFile {
+        name,
+        file_root,
+        ram_root,
+        inspector_collapsed: false,
+        data: data.into(),
+        entry_point: None,
+        entropy: OnceLock::new(),
+    })
+}
+
+/// Classifies a WASM section by its `id`, and for custom sections its `name`.
+pub(super) fn category(name: &str, id: u8) -> SectionTypeCategory {
+    match id {
+        CUSTOM if name.starts_with("Custom Section .debug") => SectionTypeCategory::Debug,
+        CUSTOM if name == "Custom Section name" => SectionTypeCategory::Symbols,
+        IMPORT | EXPORT => SectionTypeCategory::Symbols,
+        CODE => SectionTypeCategory::Code,
+        DATA => SectionTypeCategory::Data,
+        _ => SectionTypeCategory::Other,
+    }
+}
+
+fn section_name(id: u8) -> Option<&'static str> {
+    Some(match id {
+        1 => "Type",
+        IMPORT => "Import",
+        3 => "Function",
+        4 => "Table",
+        5 => "Memory",
+        6 => "Global",
+        EXPORT => "Export",
+        START => "Start",
+        9 => "Element",
+        CODE => "Code",
+        DATA => "Data",
+        DATA_COUNT => "Data Count",
+        13 => "Tag",
+        _ => return None,
+    })
+}
+
+/// Pushes a node to `segments` for each segment in the data section `payload`, which starts at `payload_start` in the file.
+/// Active segments are also pushed to `ram_segments` at their address in linear memory.
+/// The entry count has already been read.
+fn data_segments(
+    payload: &mut Reader,
+    payload_start: u64,
+    segments: &mut Vec<FileNode>,
+    ram_segments: &mut Vec<FileNode>,
+) -> Result<()> {
+    let mut index = 0;
+    while !payload.is_empty() {
+        let flags = payload.uleb()?;
+        let mut notes = vec![];
+        let address = match flags {
+            0 | 2 => {
+                if flags == 2 {
+                    notes.push(("memory".into(), payload.uleb()?.to_string()));
+                }
+                // Offsets computed from imported globals are only known at runtime.
+                if payload.u8()? != I32_CONST {
+                    return Err(anyhow!(
+                        "Data segment {index} has an offset that is not a constant"
+                    ));
+                }
+                let address = payload.sleb()? as u32 as u64;
+                if payload.u8()? != END {
+                    return Err(anyhow!(
+                        "Data segment {index} has an offset that is not a constant"
+                    ));
+                }
+                notes.push(("address".into(), format!("0x{address:x}")));
+                Some(address)
+            }
+            1 => {
+                notes.push(("mode".into(), "passive".into()));
+                None
+            }
+            _ => return Err(anyhow!("Data segment {index} has unknown flags {flags}")),
+        };
+        let len = payload.uleb()?;
+        let file_bytes_start = payload_start + payload.offset as u64;
+        payload.bytes(len)?;
+        let file_bytes_end = payload_start + payload.offset as u64;
+        let (ram_bytes_start, ram_bytes_end) = match address {
+            Some(address) => (address, checked_end(address, len)?),
+            None => (0, 0),
+        };
+        let segment = FileNode {
+            name: format!("Data Segment {index}"),
+            bytes_start: file_bytes_start,
+            bytes_end: file_bytes_end,
+            ram_bytes_start,
+            ram_bytes_end,
+            file_bytes_start,
+            file_bytes_end,
+            notes,
+            children: vec![],
+            ty: SectionType::WasmSection { id: DATA },
+            links: vec![],
+            content_hash: OnceLock::new(),
+            occurrence: 0,
+            overlaps_sibling: false,
+            inspector_collapsed: false,
+        };
+        if address.is_some() {
+            ram_segments.push(FileNode {
+                bytes_start: ram_bytes_start,
+                bytes_end: ram_bytes_end,
+                ..segment.clone()
+            });
+        }
+        segments.push(segment);
+        index += 1;
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: u64) -> Result<&'a [u8]> {
+        let bytes = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.data.get(self.offset..self.offset.checked_add(len)?))
+            .ok_or_else(|| anyhow!("Unexpected end of data at offset 0x{:x}", self.offset))?;
+        self.offset += bytes.len();
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn uleb(&mut self) -> Result<u64> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> Result<i64> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+                return Ok(result);
+            }
+        }
+    }
+
+    /// A UTF-8 string prefixed with its length.
+    fn name(&mut self) -> Result<String> {
+        let len = self.uleb()?;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+}
@@ -1,11 +1,149 @@
 #![forbid(unsafe_code)]
 
+use anyhow::Context;
 use executable_visualizer_lib::app::ExampleApp;
 use executable_visualizer_lib::sections::ExecutableFile;
+use executable_visualizer_lib::Options;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() -> eframe::Result<()> {
-    let files = vec![ExecutableFile::load_self()];
-    let app = ExampleApp::new(files);
+const USAGE: &str = "Usage: executable-visualizer [OPTION]... [--report[=md|json]] [FILE]...
+
+Opens a window inspecting each FILE, or this executable if no FILE is given.
+When FILE is -, the file is read from standard input.
+
+  --report=md    Print a Markdown table of the section sizes of each FILE, or of this executable if no FILE is given, instead of opening a window.
+                 Sections are listed largest first. Exits with a failure if any FILE can't be loaded.
+  --report=json  The same sizes as JSON, for scripts like a size regression check in CI.
+  --report       Same as --report=md.
+  --json         Same as --report=json.
+
+Options for how files are drawn:";
+
+/// How [`print_report`] formats the sizes.
+#[derive(Clone, Copy)]
+enum ReportFormat {
+    Markdown,
+    Json,
+}
+
+fn main() -> ExitCode {
+    let mut report = None;
+    let mut paths: Vec<PathBuf> = vec![];
+    let mut option_args = vec![];
+    for arg in std::env::args().skip(1) {
+        if arg == "--report" {
+            report = Some(ReportFormat::Markdown);
+        } else if arg == "--json" {
+            report = Some(ReportFormat::Json);
+        } else if let Some(format) = arg.strip_prefix("--report=") {
+            report = Some(match format {
+                "md" => ReportFormat::Markdown,
+                "json" => ReportFormat::Json,
+                _ => {
+                    eprintln!("Unsupported report format {format:?}, expected \"md\" or \"json\".");
+                    return ExitCode::FAILURE;
+                }
+            });
+        } else if arg == "--help" || arg == "-h" {
+            println!("{USAGE}\n{}", Options::ARGS_HELP);
+            return ExitCode::SUCCESS;
+        } else if arg == STDIN_PATH {
+            if paths.iter().any(|path| path.as_os_str() == STDIN_PATH) {
+                eprintln!("Standard input can only be read once");
+                return ExitCode::FAILURE;
+            }
+            paths.push(PathBuf::from(arg));
+        } else if arg.starts_with("--") {
+            option_args.push(arg);
+        } else if arg.starts_with('-') {
+            eprintln!(
+                "Unknown argument {arg:?}\n\n{USAGE}\n{}",
+                Options::ARGS_HELP
+            );
+            return ExitCode::FAILURE;
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    let options = match Options::from_args(&option_args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}\n{}", Options::ARGS_HELP);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(format) = report {
+        print_report(&paths, format)
+    } else {
+        match run_gui(&paths, options, option_args.is_empty()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+fn print_report(paths: &[PathBuf], format: ReportFormat) -> ExitCode {
+    let files = if paths.is_empty() {
+        vec![ExecutableFile::load_self()]
+    } else {
+        let mut files = vec![];
+        for path in paths {
+            match load(path) {
+                Ok(file) => files.push(file),
+                Err(err) => {
+                    eprintln!("{err:?}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        files
+    };
+    match format {
+        ReportFormat::Markdown => {
+            let tables: Vec<String> = files
+                .iter()
+                .map(|file| executable_visualizer_lib::report::markdown(file, None))
+                .collect();
+            print!("{}", tables.join("\n"));
+        }
+        ReportFormat::Json => print!("{}", executable_visualizer_lib::report::json(&files)),
+    }
+    ExitCode::SUCCESS
+}
+
+/// The path given on the command line to read a file from standard input instead.
+const STDIN_PATH: &str = "-";
+
+/// Like [`ExecutableFile::load`], but reads standard input when `path` is [`STDIN_PATH`].
+fn load(path: &Path) -> anyhow::Result<ExecutableFile> {
+    if path.as_os_str() != STDIN_PATH {
+        return ExecutableFile::load(path);
+    }
+    let mut data = vec![];
+    std::io::stdin()
+        .read_to_end(&mut data)
+        .context("Failed to read standard input")?;
+    ExecutableFile::load_from_bytes("<stdin>".to_owned(), &data)
+}
+
+/// Options given on the command line replace the ones saved by the last session, otherwise those are restored.
+/// Files that fail to load are reported on stderr and left out, so the others can still be inspected.
+fn run_gui(paths: &[PathBuf], options: Options, restore_options: bool) -> eframe::Result<()> {
+    let files = if paths.is_empty() {
+        vec![ExecutableFile::load_self()]
+    } else {
+        paths
+            .iter()
+            .filter_map(|path| load(path).map_err(|err| eprintln!("{err:#}")).ok())
+            .collect()
+    };
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([320.0, 240.0]),
@@ -14,6 +152,12 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Executable Inspector",
         native_options,
-        Box::new(|_cc| Box::new(app)),
+        Box::new(move |cc| {
+            Box::new(if restore_options {
+                ExampleApp::restore(files, cc.storage)
+            } else {
+                ExampleApp::with_options(files, options)
+            })
+        }),
     )
 }
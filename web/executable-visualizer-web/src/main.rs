@@ -1,5 +1,6 @@
 #![forbid(unsafe_code)]
 
+use anyhow::{anyhow, Result};
 use eframe::WebRunner;
 use executable_visualizer_lib::app::ExampleApp;
 use executable_visualizer_lib::sections::ExecutableFile;
@@ -8,6 +9,16 @@ use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{HtmlElement, Request, RequestInit, RequestMode, Response};
 
+/// Files fetched at startup when the page URL doesn't list any, separated by commas.
+/// Set the `EXAMPLE_FILES` environment variable at build time to host the demo with other examples.
+const DEFAULT_EXAMPLE_FILES: &str = match option_env!("EXAMPLE_FILES") {
+    Some(files) => files,
+    None => "x86-executable-visualizer",
+};
+
+/// At most this many files are fetched at startup, so a long `?files=` list can't stall the page.
+const MAX_STARTUP_FILES: usize = 8;
+
 pub fn main() {
     wasm_bindgen_futures::spawn_local(run());
 }
@@ -36,41 +47,80 @@ async fn run() {
         .set_css_text("margin: 0; height: 100%; width: 100%");
 
     let mut files = vec![];
-    let data = load_example_binary("x86-executable-visualizer").await;
-    if let Ok(file) = ExecutableFile::load_from_bytes("x86-executable-visualizer".to_owned(), &data)
-    {
-        files.push(file);
+    for name in startup_file_names() {
+        let file = match load_example_binary(&name).await {
+            Ok(data) => ExecutableFile::load_from_bytes(name.clone(), &data),
+            Err(err) => Err(err),
+        };
+        match file {
+            Ok(file) => files.push(file),
+            Err(err) => web_sys::console::warn_1(&format!("Failed to load {name}: {err:?}").into()),
+        }
     }
 
-    let app = ExampleApp::new(files);
     let runner = WebRunner::new();
     runner
         .start(
             "the-id",
             eframe::WebOptions::default(),
-            Box::new(|_| Box::new(app)),
+            Box::new(|cc| Box::new(ExampleApp::restore(files, cc.storage))),
         )
         .await
         .unwrap();
 }
 
-async fn load_example_binary(name: &str) -> Vec<u8> {
+/// The files listed in the `files` URL parameter, e.g. `?files=a.out,libfoo.so`, or [`DEFAULT_EXAMPLE_FILES`].
+fn startup_file_names() -> Vec<String> {
+    let search = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
+    let from_url = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|param| param.strip_prefix("files="))
+        .map(|files| {
+            js_sys::decode_uri_component(files)
+                .map(String::from)
+                .unwrap_or_else(|_| files.to_owned())
+        });
+    from_url
+        .as_deref()
+        .unwrap_or(DEFAULT_EXAMPLE_FILES)
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .take(MAX_STARTUP_FILES)
+        .map(str::to_owned)
+        .collect()
+}
+
+async fn load_example_binary(name: &str) -> Result<Vec<u8>> {
     let mut opts = RequestInit::new();
     opts.method("GET");
     opts.mode(RequestMode::Cors);
 
-    let request = Request::new_with_str_and_init(name, &opts).unwrap();
+    let request = Request::new_with_str_and_init(name, &opts)
+        .map_err(|err| anyhow!("Invalid request: {err:?}"))?;
 
-    let window = web_sys::window().unwrap();
+    let window = web_sys::window().ok_or_else(|| anyhow!("No window"))?;
     let resp_value = JsFuture::from(window.fetch_with_request(&request))
         .await
-        .unwrap();
+        .map_err(|err| anyhow!("Fetch failed: {err:?}"))?;
 
     // `resp_value` is a `Response` object.
-    assert!(resp_value.is_instance_of::<Response>());
-    let resp: Response = resp_value.dyn_into().unwrap();
+    let resp: Response = resp_value
+        .dyn_into()
+        .map_err(|_| anyhow!("Fetch did not return a Response"))?;
+    if !resp.ok() {
+        return Err(anyhow!("Server responded with status {}", resp.status()));
+    }
 
     // Convert this other `Promise` into a rust `Future`.
-    let js_value = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
-    Uint8Array::new(&js_value).to_vec()
+    let buffer = resp
+        .array_buffer()
+        .map_err(|err| anyhow!("Failed to read response: {err:?}"))?;
+    let js_value = JsFuture::from(buffer)
+        .await
+        .map_err(|err| anyhow!("Failed to read response: {err:?}"))?;
+    Ok(Uint8Array::new(&js_value).to_vec())
 }